@@ -1,110 +1,1053 @@
+use std::collections::HashSet;
 use std::io;
-use std::sync::{mpsc, mpsc::Receiver, Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::process::{self, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use chrono::{DateTime, Local};
-use crossterm::event::{self, Event, KeyCode};
+use chrono::{DateTime, Utc};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use ratatui::{
-    backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    backend::{Backend, TestBackend},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Position, Rect},
+    style::{Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, List, ListItem, Paragraph, Row, Table},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, BorderType, Borders, Cell, Chart, Clear, Dataset,
+        GraphType, List, ListItem, ListState, Padding, Paragraph, Row, Sparkline, Table, Wrap,
+    },
     Frame, Terminal,
 };
 
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+use crate::astronomy::{Moon, Sun};
+use crate::clock::Clock;
+use crate::config::{Config, Fallback};
+use crate::keymap::{Action, Keymap};
+use crate::layout::{Node, Size, Widget};
+use crate::noaa;
 use crate::noaa::alerts;
 use crate::noaa::forecast;
 use crate::noaa::observation;
 use crate::noaa::station;
+use crate::snapshot::{Saved, Snapshot};
+use crate::source::WeatherSource;
+use crate::theme::Theme;
 use crate::units::direction::degree_to_compass;
-use crate::units::speed::kph2mph;
-use crate::units::temperature::c2f;
+use crate::units::temperature;
+use crate::units::Units;
 
 const MISSING: &str = "--";
 
-type WeatherData = (
-    observation::Observation,
-    station::Station,
-    alerts::Alerts,
-    forecast::Forecast,
-);
+/// The outcome of one refresh, along with the client's request statistics. A resource is `None`
+/// when it was not requested because something it depends on failed.
+pub struct Update {
+    /// The station this update was fetched for.
+    pub id: String,
+
+    pub observation: Result<observation::Observation, noaa::Error>,
+    pub station: Result<station::Station, noaa::Error>,
+    pub alerts: Option<Result<alerts::Alerts, noaa::Error>>,
+    pub forecast: Option<Result<forecast::Forecast, noaa::Error>>,
+    pub hourly_forecast: Option<Result<forecast::Forecast, noaa::Error>>,
+    pub stats: noaa::Stats,
+}
+
+impl Update {
+    /// Fetches everything the dashboard shows for `station`.
+    pub fn fetch(source: &dyn WeatherSource, station: &str) -> Self {
+        let observation = source.observation(station);
+        let stat = source.station(station);
+        let (alerts, forecast, hourly_forecast) = match stat {
+            Ok(ref stat) => (
+                Some(source.alerts(stat)),
+                Some(source.forecast(stat)),
+                Some(source.hourly_forecast(stat)),
+            ),
+            Err(_) => (None, None, None),
+        };
+        Self {
+            id: station.to_owned(),
+            observation,
+            station: stat,
+            alerts,
+            forecast,
+            hourly_forecast,
+            stats: source.stats(),
+        }
+    }
+
+    /// Fills in what the observation lacks, or everything if it is stale, from the nearest
+    /// stations with fresh observations. Nothing changes if they can't be fetched.
+    pub fn fill_in(&mut self, source: &dyn WeatherSource, fallback: &Fallback, now: DateTime<Utc>) {
+        let (Ok(observation), Ok(station)) = (&mut self.observation, &self.station) else {
+            return;
+        };
+        if !fallback.enabled {
+            return;
+        }
+        // An age too large for chrono is no limit at all.
+        let max_age = chrono::Duration::from_std(fallback.max_age()).ok();
+        let fresh = |properties: &observation::Properties| {
+            DateTime::parse_from_rfc3339(&properties.timestamp)
+                .is_ok_and(|time| max_age.is_none_or(|max_age| now - time.to_utc() <= max_age))
+        };
+        let properties = &mut observation.properties;
+        let stale = !fresh(properties);
+        if !properties.wants(stale) {
+            return;
+        }
+        let Ok(nearby) = source.nearby_stations(station) else {
+            return;
+        };
+        let others = nearby.iter().filter(|other| other.id() != station.id());
+        for other in others.take(fallback.stations) {
+            match source.nearby_observation(other.id()) {
+                Ok(observation) if fresh(&observation.properties) => {
+                    properties.fill_from(&observation.properties, other.id(), stale);
+                }
+                _ => continue,
+            }
+            if !properties.wants(stale) {
+                break;
+            }
+        }
+        self.stats = source.stats();
+    }
+
+    /// Whether the station or the observation arrived.
+    pub fn arrived(&self) -> bool {
+        self.observation.is_ok() || self.station.is_ok()
+    }
+}
+
+/// The last good value of a resource, kept across failed refreshes.
+struct Resource<T> {
+    value: Option<T>,
+    fetched_at: Option<DateTime<Utc>>,
+    failures: u32,
+    error: Option<noaa::Error>,
+}
+
+impl<T> Default for Resource<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            fetched_at: None,
+            failures: 0,
+            error: None,
+        }
+    }
+}
+
+impl<T> Resource<T> {
+    fn update(&mut self, result: Option<Result<T, noaa::Error>>, now: DateTime<Utc>) {
+        match result {
+            Some(Ok(value)) => {
+                self.value = Some(value);
+                self.fetched_at = Some(now);
+                self.failures = 0;
+                self.error = None;
+            }
+            Some(Err(err)) => {
+                self.failures += 1;
+                self.error = Some(err);
+            }
+            None => self.failures += 1,
+        }
+    }
+
+    /// Seeds the resource from a snapshot, unless something newer already arrived.
+    fn restore(&mut self, saved: Option<Saved<T>>) {
+        if let (None, Some(saved)) = (&self.value, saved) {
+            self.value = Some(saved.value);
+            self.fetched_at = Some(saved.fetched_at);
+        }
+    }
+
+    /// Whether the value is left over from before a failed refresh.
+    fn is_stale(&self) -> bool {
+        self.value.is_some() && self.failures > 0
+    }
+
+    /// Panel title suffix describing a stale value, e.g. "fetched 5m ago, refresh failed 2x".
+    fn staleness(
+        &self,
+        verb: &str,
+        since: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if !self.is_stale() {
+            return None;
+        }
+        let age = since
+            .or(self.fetched_at)
+            .map_or(MISSING.to_string(), |time| format_age(now - time));
+        Some(format!(
+            "{verb} {age} ago, refresh failed {}x",
+            self.failures
+        ))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    observation: Resource<observation::Observation>,
+    station: Resource<station::Station>,
+    alerts: Resource<alerts::Alerts>,
+    forecast: Resource<forecast::Forecast>,
+    hourly_forecast: Resource<forecast::Forecast>,
+    stats: noaa::Stats,
+
+    /// Saved data to fall back on if the network is down at start-up.
+    fallback: Option<Snapshot>,
+
+    /// Set while showing saved data, to the time that data was fetched.
+    offline: Option<DateTime<Utc>>,
+
+    /// Barometric pressure readings, oldest first, to show the trend.
+    pressure: Vec<(DateTime<Utc>, f32)>,
+
+    /// When the web worker will next refresh, while online.
+    next_refresh: Option<DateTime<Utc>>,
+
+    clock: Clock,
+    config: Config,
+    theme: Theme,
+    keymap: Keymap,
+}
+
+impl State {
+    fn apply(&mut self, update: Update) {
+        if update.arrived() {
+            self.offline = None;
+        }
+        let now = self.clock.now();
+        self.observation.update(Some(update.observation), now);
+        self.record_pressure();
+        self.station.update(Some(update.station), now);
+        self.station_zone();
+        let filter = &self.config.alerts;
+        self.alerts.update(
+            update
+                .alerts
+                .map(|alerts| alerts.map(|alerts| filter.apply(alerts))),
+            now,
+        );
+        self.forecast.update(update.forecast, now);
+        self.hourly_forecast.update(update.hourly_forecast, now);
+        self.stats = update.stats;
+        self.next_refresh = chrono::Duration::from_std(self.config.refresh.interval())
+            .ok()
+            .and_then(|interval| now.checked_add_signed(interval));
+    }
+
+    /// Shows the saved data if there is nothing else to show because the network is down.
+    fn fall_back(&mut self) {
+        if !self.has_data() && self.error().is_some_and(noaa::Error::is_unreachable) {
+            if let Some(snapshot) = self.fallback.take() {
+                self.restore(snapshot);
+            }
+        }
+    }
+
+    /// Tells the clock the station's zone, for `time_zone = "station"`.
+    fn station_zone(&mut self) {
+        let station = self.station.value.as_ref();
+        if let Some(zone) = station.and_then(|station| station.properties.time_zone.as_deref()) {
+            self.clock.set_station_zone(zone);
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.offline = snapshot.fetched_at();
+        self.observation.restore(snapshot.observation);
+        self.record_pressure();
+        self.station.restore(snapshot.station);
+        self.station_zone();
+        self.alerts.restore(snapshot.alerts.map(|saved| Saved {
+            value: self.config.alerts.apply(saved.value),
+            ..saved
+        }));
+        self.forecast.restore(snapshot.forecast);
+        self.hourly_forecast.restore(snapshot.hourly_forecast);
+    }
+
+    /// Adds the latest observation's pressure to the history, if it is a new reading.
+    fn record_pressure(&mut self) {
+        let Some(ref observation) = self.observation.value else {
+            return;
+        };
+        let properties = &observation.properties;
+        // A nearby station's pressure would make a jump in the history.
+        let (Ok(time), Some(pressure), None) = (
+            DateTime::parse_from_rfc3339(&properties.timestamp),
+            properties.barometric_pressure.value,
+            &properties.barometric_pressure.source,
+        ) else {
+            return;
+        };
+        let time = time.to_utc();
+        if self.pressure.last().is_some_and(|&(last, _)| last >= time) {
+            return;
+        }
+        self.pressure.push((time, pressure));
+        let oldest = time - chrono::Duration::from_std(PRESSURE_HISTORY).unwrap();
+        self.pressure.retain(|&(time, _)| time >= oldest);
+    }
+
+    fn has_data(&self) -> bool {
+        self.observation.value.is_some() || self.station.value.is_some()
+    }
+
+    /// The error to show when there is nothing else to draw.
+    fn error(&self) -> Option<&noaa::Error> {
+        self.station
+            .error
+            .as_ref()
+            .or(self.observation.error.as_ref())
+    }
+}
+
+/// How often the screen is redrawn when nothing else happens, e.g. to animate the spinner.
+const TICK: Duration = Duration::from_millis(100);
+
+/// How long to wait on quitting for a refresh in progress, so its snapshot gets saved.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// How many items PgUp and PgDn move. Items are several rows tall, so this is about a screenful.
+const PAGE: isize = 3;
+
+/// Shown by the help overlay after the keys, which can't be rebound.
+const MOUSE_HELP: (&str, &str) = ("Mouse", "Click to focus and select, scroll the list below");
+
+/// Everything that changes the app, sent to the UI thread by the workers.
+enum Message {
+    /// A refresh where at least the station or the observation arrived.
+    DataArrived(Update),
+
+    /// A refresh where nothing arrived.
+    FetchFailed(Update),
+
+    Key(KeyEvent),
+
+    Mouse(MouseEvent),
+
+    /// No terminal event arrived within [`TICK`].
+    Tick,
+
+    Resize,
+
+    /// SIGINT, SIGTERM or SIGHUP.
+    Quit,
+
+    /// A worker panicked with this message, and the app can't carry on without it.
+    Crashed(String),
+}
+
+/// The panels that can take focus, in Tab order.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Panel {
+    #[default]
+    Current,
+    Alerts,
+    Forecast,
+}
+
+impl Panel {
+    const ALL: [Panel; 3] = [Panel::Current, Panel::Alerts, Panel::Forecast];
+
+    fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&panel| panel == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|&panel| panel == self).unwrap();
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// The views at the top of the screen, switched between with `1` and `2` or a click.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Screen {
+    #[default]
+    Dashboard,
+
+    /// The hourly charts on their own, for terminals too narrow to show them on the dashboard.
+    Hourly,
+}
+
+impl Screen {
+    const ALL: [Screen; 2] = [Screen::Dashboard, Screen::Hourly];
+
+    fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&screen| screen == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let i = Self::ALL.iter().position(|&screen| screen == self).unwrap();
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Screen::Dashboard => "Dashboard",
+            Screen::Hourly => "Hourly",
+        }
+    }
+}
+
+/// Something on screen that reacts to the mouse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    Screen(Screen),
+    Panel(Panel),
+
+    /// An item of the list in a panel.
+    Item(Panel, usize),
+}
+
+/// How the user is looking at the data, as opposed to the data itself in [`State`].
+#[derive(Debug, Default)]
+struct View {
+    screen: Screen,
+    focus: Panel,
+    alerts: ListState,
+    forecast: ListState,
+
+    /// Where the last frame drew each [`Target`], innermost last.
+    targets: Vec<(Rect, Target)>,
+
+    /// Whether the details of the selected item in the focused list are open.
+    details: bool,
+
+    /// Whether the station's details are open.
+    info: bool,
+
+    help: bool,
+}
+
+impl View {
+    fn popup_open(&self) -> bool {
+        self.details || self.info || self.help
+    }
+
+    fn close_popups(&mut self) {
+        self.details = false;
+        self.info = false;
+        self.help = false;
+    }
+}
+
+/// The state of the UI thread. Only the UI thread touches it; workers send [`Message`]s.
+struct App {
+    station: String,
+
+    /// The stations the switch-station key cycles through, starting with the first shown.
+    stations: Vec<String>,
+
+    /// Set when the user switches station, until the web worker has been told.
+    switched: bool,
+
+    state: State,
+    view: View,
+
+    /// Ticks seen so far, which drive the loading spinner.
+    ticks: usize,
+
+    /// Set when the user asks for a refresh, until the web worker has been woken.
+    refresh: bool,
+
+    /// Alerts that the `on_alert` hook has already run for.
+    seen_alerts: HashSet<String>,
+
+    /// Whether to draw the compact dashboard of `--inline`.
+    inline: bool,
+
+    quit: bool,
+}
+
+impl App {
+    fn new(station: &str, state: State) -> Self {
+        Self {
+            station: station.to_owned(),
+            stations: vec![station.to_owned()],
+            switched: false,
+            state,
+            view: View::default(),
+            ticks: 0,
+            refresh: false,
+            seen_alerts: HashSet::new(),
+            inline: false,
+            quit: false,
+        }
+    }
+
+    fn update(&mut self, message: Message) {
+        match message {
+            // A refresh that was under way when the user switched station.
+            Message::DataArrived(update) | Message::FetchFailed(update)
+                if update.id != self.station => {}
+            Message::DataArrived(update) => {
+                self.state.apply(update);
+                self.run_hooks();
+            }
+            Message::FetchFailed(update) => {
+                self.state.apply(update);
+                self.state.fall_back();
+            }
+            Message::Key(key) => self.key(key),
+            Message::Mouse(mouse) => self.mouse(mouse),
+            Message::Tick => self.ticks += 1,
+            // The next draw picks up the new size.
+            Message::Resize => (),
+            Message::Quit | Message::Crashed(_) => self.quit = true,
+        }
+    }
+
+    /// Runs the `on_alert` hook for alerts not seen before.
+    fn run_hooks(&mut self) {
+        let Some(ref alerts) = self.state.alerts.value else {
+            return;
+        };
+        for alert in &alerts.features {
+            let properties = &alert.properties;
+            let key = if alert.id.is_empty() {
+                format!("{} {}", properties.event, properties.onset)
+            } else {
+                alert.id.clone()
+            };
+            if !self.seen_alerts.insert(key) {
+                continue;
+            }
+            if let Some(ref command) = self.state.config.hooks.on_alert {
+                let headline = properties.headline.as_deref().unwrap_or(&properties.event);
+                run_hook(
+                    command,
+                    &[
+                        ("WX_STATION", &self.station),
+                        ("WX_EVENT", &properties.event),
+                        ("WX_SEVERITY", &properties.severity),
+                        ("WX_HEADLINE", headline),
+                    ],
+                );
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        // Raw mode keeps Ctrl-C from raising SIGINT, so it quits here whatever the bindings.
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+        let Some(action) = self.state.keymap.action(&key) else {
+            return;
+        };
+        if action == Action::Quit {
+            self.quit = true;
+            return;
+        }
+
+        if self.view.popup_open() {
+            if matches!(
+                action,
+                Action::Close | Action::OpenDetail | Action::StationInfo | Action::Help
+            ) {
+                self.view.close_popups();
+            }
+            return;
+        }
+        match action {
+            Action::Dashboard => self.view.screen = Screen::Dashboard,
+            Action::Hourly => self.view.screen = Screen::Hourly,
+            Action::NextTab => self.view.screen = self.view.screen.next(),
+            Action::PreviousTab => self.view.screen = self.view.screen.previous(),
+            Action::Refresh => {
+                self.refresh = true;
+                if let Some(ref mut next) = self.state.next_refresh {
+                    *next = self.state.clock.now();
+                }
+            }
+            Action::Help => self.view.help = true,
+            Action::StationInfo => self.view.info = true,
+            Action::ToggleUnits => {
+                let units = &mut self.state.config.units;
+                *units = match units {
+                    Units::Imperial => Units::Metric,
+                    Units::Metric => Units::Imperial,
+                };
+            }
+            Action::SwitchStation => self.switch_station(),
+            _ if self.view.screen != Screen::Dashboard => (),
+            Action::NextPanel => self.focus(self.view.focus.next()),
+            Action::PreviousPanel => self.focus(self.view.focus.previous()),
+            Action::Down => self.scroll(1),
+            Action::Up => self.scroll(-1),
+            Action::PageDown => self.scroll(PAGE),
+            Action::PageUp => self.scroll(-PAGE),
+            Action::Top => self.select_end(false),
+            Action::Bottom => self.select_end(true),
+            Action::OpenDetail => self.view.details = self.selected().is_some(),
+            Action::Close | Action::Quit => (),
+        }
+    }
+
+    /// Shows the next of [`App::stations`], starting afresh as if it had been given at start-up.
+    fn switch_station(&mut self) {
+        let Some(i) = self.stations.iter().position(|s| *s == self.station) else {
+            return;
+        };
+        let next = &self.stations[(i + 1) % self.stations.len()];
+        if *next == self.station {
+            return;
+        }
+        self.station = next.clone();
+        let state = mem::take(&mut self.state);
+        self.state = State {
+            clock: state.clock,
+            config: state.config,
+            theme: state.theme,
+            keymap: state.keymap,
+            ..Default::default()
+        };
+        self.view = View {
+            screen: self.view.screen,
+            ..Default::default()
+        };
+        self.seen_alerts.clear();
+        self.switched = true;
+    }
+
+    fn mouse(&mut self, mouse: MouseEvent) {
+        let target = self
+            .view
+            .targets
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position::new(mouse.column, mouse.row)))
+            .map(|&(_, target)| target);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.view.popup_open() => {
+                self.view.close_popups();
+            }
+            MouseEventKind::Down(MouseButton::Left) => match target {
+                Some(Target::Screen(screen)) => self.view.screen = screen,
+                Some(Target::Panel(panel)) => self.focus(panel),
+                Some(Target::Item(panel, i)) => {
+                    self.view.focus = panel;
+                    if let Some((list, _)) = self.list(panel) {
+                        list.select(Some(i));
+                    }
+                }
+                None => (),
+            },
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let delta = if mouse.kind == MouseEventKind::ScrollDown {
+                    1
+                } else {
+                    -1
+                };
+                if let Some(Target::Panel(panel) | Target::Item(panel, _)) = target {
+                    self.scroll_list(panel, delta);
+                }
+            }
+            _ => (),
+        }
+    }
 
+    /// The list state and length of `panel`, if it is a list.
+    fn list(&mut self, panel: Panel) -> Option<(&mut ListState, usize)> {
+        match panel {
+            Panel::Current => None,
+            Panel::Alerts => {
+                let len = self
+                    .state
+                    .alerts
+                    .value
+                    .as_ref()
+                    .map_or(0, |alerts| alerts.features.len());
+                Some((&mut self.view.alerts, len))
+            }
+            Panel::Forecast => {
+                let len = self
+                    .state
+                    .forecast
+                    .value
+                    .as_ref()
+                    .map_or(0, |forecast| forecast.properties.periods.len());
+                Some((&mut self.view.forecast, len))
+            }
+        }
+    }
+
+    /// Focuses `panel`, selecting the first item of a list that has no selection yet.
+    fn focus(&mut self, panel: Panel) {
+        self.view.focus = panel;
+        self.scroll(0);
+    }
+
+    /// Moves the selection in the focused list by `delta` items.
+    fn scroll(&mut self, delta: isize) {
+        self.scroll_list(self.view.focus, delta);
+    }
+
+    /// Moves the selection in the list of `panel` by `delta` items, staying within the list.
+    fn scroll_list(&mut self, panel: Panel, delta: isize) {
+        if let Some((list, len)) = self.list(panel) {
+            if len == 0 {
+                list.select(None);
+                return;
+            }
+            let selected = list.selected().unwrap_or(0) as isize + delta;
+            list.select(Some(selected.clamp(0, len as isize - 1) as usize));
+        }
+    }
+
+    /// Selects the first or, with `last`, the last item of the focused list.
+    fn select_end(&mut self, last: bool) {
+        if let Some((list, len)) = self.list(self.view.focus) {
+            list.select((len > 0).then(|| if last { len - 1 } else { 0 }));
+        }
+    }
+
+    /// The selected item of the focused list, if it still exists.
+    fn selected(&mut self) -> Option<usize> {
+        let (list, len) = self.list(self.view.focus)?;
+        list.selected().filter(|&i| i < len)
+    }
+
+    fn draw(&mut self, f: &mut Frame) {
+        if !self.state.has_data() && self.state.error().is_none() {
+            loading(f, self.ticks);
+        } else if self.inline {
+            inline_ui(f, &self.station, &self.state, &mut self.view);
+        } else {
+            draw(f, &self.station, &self.state, &mut self.view);
+        }
+    }
+}
+
+/// How the app runs, from the command line and the config file.
+pub struct Options {
+    /// Show the saved data and never contact NOAA.
+    pub offline: bool,
+
+    /// Draw the compact dashboard, for an inline viewport below the prompt.
+    pub inline: bool,
+
+    pub clock: Clock,
+    pub config: Config,
+    pub theme: Theme,
+    pub keymap: Keymap,
+}
+
+/// Runs the TUI. With `offline` set the app shows `snapshot` and never contacts NOAA; otherwise
+/// `snapshot` is only used if the network turns out to be down. Returns the station shown last.
 pub fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     station: &str,
-    get_data: fn(&str) -> WeatherData,
-) -> io::Result<()> {
-    let weather_data = Arc::new(Mutex::new(None));
-    let rx = start_workers(weather_data.clone(), station, get_data);
-    let mut loading_counter: usize = 0;
-    loop {
-        let data = weather_data.lock().unwrap();
-        if let Some(ref data) = *data {
-            terminal.draw(|f| ui(f, &data.0, &data.1, &data.2, &data.3))?;
-
-            match rx.recv().unwrap() {
-                AppEvent::Redraw => (),
-                AppEvent::Exit => return Ok(()),
+    source: Box<dyn WeatherSource>,
+    snapshot: Option<Snapshot>,
+    options: Options,
+) -> io::Result<String> {
+    let Options {
+        offline,
+        inline,
+        clock,
+        config,
+        theme,
+        keymap,
+    } = options;
+    let refresh = config.refresh.interval();
+    let mut stations = vec![station.to_owned()];
+    if !offline {
+        for location in config.locations.values() {
+            if !stations.contains(location) {
+                stations.push(location.clone());
             }
-        } else {
-            drop(data); // If we don't have data to read, drop the lock so we don't stop the worker
-                        // from updating.
-            terminal.draw(|f| loading(f, loading_counter))?;
-            loading_counter += 1;
-            thread::sleep(Duration::from_millis(100));
-            match rx.try_recv() {
-                Ok(AppEvent::Redraw) | Err(mpsc::TryRecvError::Empty) => (),
-                Ok(AppEvent::Exit) => return Ok(()),
-                _ => panic!("Thread crashed"),
+        }
+    }
+    let mut state = State {
+        clock,
+        config,
+        theme,
+        keymap,
+        ..Default::default()
+    };
+    match snapshot {
+        Some(snapshot) if offline => state.restore(snapshot),
+        snapshot => state.fallback = snapshot,
+    }
+    let mut app = App::new(station, state);
+    app.stations = stations;
+    app.inline = inline;
+
+    let fallback = app.state.config.fallback.clone();
+    let clock = app.state.clock.clone();
+    let workers = Workers::start(station, source, !offline, refresh, fallback, clock)?;
+    let result = (|| {
+        while !app.quit {
+            terminal.draw(|f| app.draw(f))?;
+            let message = workers
+                .rx
+                .recv()
+                .map_err(|_| io::Error::other("event thread stopped"))?;
+            if let Message::Crashed(ref panic) = message {
+                return Err(io::Error::other(format!(
+                    "a worker thread panicked: {panic}"
+                )));
+            }
+            app.update(message);
+            if mem::take(&mut app.switched) {
+                workers.switch(&app.station);
+            } else if mem::take(&mut app.refresh) {
+                workers.refresh();
+            }
+        }
+        Ok(())
+    })();
+    workers.stop();
+    result.map(|()| app.station)
+}
+
+/// Fetches once and draws the dashboard off-screen, for `wx snapshot`. As with [`run_app`], with
+/// `offline` set it draws `snapshot` instead, which otherwise stands in if the network is down.
+pub fn render_once(
+    station: &str,
+    source: &dyn WeatherSource,
+    snapshot: Option<Snapshot>,
+    options: Options,
+    width: u16,
+    height: u16,
+) -> io::Result<Buffer> {
+    let Options {
+        offline,
+        clock,
+        config,
+        theme,
+        keymap,
+        ..
+    } = options;
+    let mut state = State {
+        clock,
+        config,
+        theme,
+        keymap,
+        ..Default::default()
+    };
+    match snapshot {
+        Some(snapshot) if offline => state.restore(snapshot),
+        snapshot => {
+            state.fallback = snapshot;
+            let mut update = Update::fetch(source, station);
+            update.fill_in(source, &state.config.fallback, state.clock.now());
+            let arrived = update.arrived();
+            state.apply(update);
+            if !arrived {
+                state.fall_back();
             }
+            // Nothing will refresh it.
+            state.next_refresh = None;
         }
     }
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|f| draw(f, station, &state, &mut View::default()))?;
+    Ok(terminal.backend().buffer().clone())
 }
 
-enum AppEvent {
-    Redraw,
-    Exit,
+/// Draws everything but the loading screen.
+fn draw(f: &mut Frame, station: &str, state: &State, view: &mut View) {
+    view.targets.clear();
+    let Some(breakpoint) = Breakpoint::of(f.area()) else {
+        too_small(f, &state.theme);
+        return;
+    };
+    match state.error() {
+        Some(err) if !state.has_data() => error_panel(f, station, err, &state.theme),
+        _ => ui(f, state, view, breakpoint),
+    }
+    status_line(f, &state.stats, &state.clock, &state.theme);
+    if let Some(time) = state.offline {
+        offline_banner(f, &state.clock.date_time(&time), &state.theme);
+    }
 }
 
-fn start_workers(
-    weather_data: Arc<Mutex<Option<WeatherData>>>,
-    station: &str,
-    get_data: fn(&str) -> WeatherData,
-) -> Receiver<AppEvent> {
-    let (tx, rx) = mpsc::channel();
+/// Why the UI thread woke the web worker.
+enum Wake {
+    Refresh,
 
-    // Web request worker.
-    let web_tx = tx.clone();
-    let station = station.to_owned();
-    thread::spawn(move || loop {
-        let data = get_data(&station);
-        weather_data.lock().unwrap().replace(data);
-        _ = web_tx.send(AppEvent::Redraw);
-        thread::sleep(Duration::from_secs(10));
-    });
+    /// The user switched to another station.
+    Station(String),
+}
 
-    // Handle TUI events.
-    let event_tx = tx.clone();
-    thread::spawn(move || loop {
-        match event::read().unwrap() {
-            Event::Key(key) => {
-                if let KeyCode::Char('q') = key.code {
-                    _ = event_tx.send(AppEvent::Exit);
+/// Runs `work` on a thread of its own. A panic is sent on to the UI thread, which owns the
+/// terminal and can put it back before reporting it.
+fn spawn_worker(tx: Sender<Message>, work: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    thread::spawn(move || {
+        if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(work)) {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            _ = tx.send(Message::Crashed(message));
+        }
+    })
+}
+
+/// The threads that feed the UI thread.
+struct Workers {
+    rx: Receiver<Message>,
+
+    /// Sending wakes the web worker early. Dropping it tells the worker to stop.
+    wake: Sender<Wake>,
+
+    web: Option<JoinHandle<()>>,
+    signals: signal_hook::iterator::Handle,
+}
+
+impl Workers {
+    /// Starts the workers. When `online`, the web worker refreshes every `refresh`, filling in
+    /// the observation as `fallback` says, by the clock alongside it.
+    fn start(
+        station: &str,
+        source: Box<dyn WeatherSource>,
+        online: bool,
+        refresh: Duration,
+        fallback: Fallback,
+        clock: Clock,
+    ) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let (wake, woken) = mpsc::channel();
+
+        // Web request worker.
+        let web = online.then(|| {
+            let web_tx = tx.clone();
+            let mut station = station.to_owned();
+            spawn_worker(tx.clone(), move || loop {
+                // Refreshes asked for during the last one are covered by this one.
+                for wake in woken.try_iter() {
+                    if let Wake::Station(next) = wake {
+                        station = next;
+                    }
+                }
+                let mut update = Update::fetch(source.as_ref(), &station);
+                update.fill_in(source.as_ref(), &fallback, clock.now());
+                let message = if update.arrived() {
+                    Message::DataArrived(update)
+                } else {
+                    Message::FetchFailed(update)
+                };
+                if web_tx.send(message).is_err() {
+                    return;
+                }
+                match woken.recv_timeout(refresh) {
+                    Ok(Wake::Station(next)) => station = next,
+                    Ok(Wake::Refresh) | Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            })
+        });
+
+        // Turn signals into a clean quit, so the terminal is restored and the station saved.
+        let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+        let handle = signals.handle();
+        let signal_tx = tx.clone();
+        spawn_worker(tx.clone(), move || {
+            for _ in signals.forever() {
+                if signal_tx.send(Message::Quit).is_err() {
+                    return;
                 }
             }
-            Event::Resize(_, _) => {
-                _ = event_tx.send(AppEvent::Redraw);
+        });
+
+        // Handle TUI events. Polling rather than blocking lets the thread notice when the UI
+        // thread hangs up.
+        spawn_worker(tx.clone(), move || loop {
+            let message = match event::poll(TICK) {
+                Ok(true) => match event::read() {
+                    Ok(Event::Key(key)) => Message::Key(key),
+                    Ok(Event::Mouse(mouse)) => Message::Mouse(mouse),
+                    Ok(Event::Resize(_, _)) => Message::Resize,
+                    Ok(_) => continue,
+                    Err(_) => return,
+                },
+                Ok(false) => Message::Tick,
+                Err(_) => return,
+            };
+            if tx.send(message).is_err() {
+                return;
+            }
+        });
+
+        Ok(Self {
+            rx,
+            wake,
+            web,
+            signals: handle,
+        })
+    }
+
+    /// Asks for a refresh now rather than at the next interval. Does nothing when offline.
+    fn refresh(&self) {
+        _ = self.wake.send(Wake::Refresh);
+    }
+
+    /// Has the web worker fetch `station` from now on, starting straight away.
+    fn switch(&self, station: &str) {
+        _ = self.wake.send(Wake::Station(station.to_owned()));
+    }
+
+    /// Stops the workers, giving a refresh in progress [`SHUTDOWN_GRACE`] to finish.
+    fn stop(self) {
+        self.signals.close();
+        drop(self.wake);
+        drop(self.rx);
+        if let Some(web) = self.web {
+            let deadline = Instant::now() + SHUTDOWN_GRACE;
+            while !web.is_finished() && Instant::now() < deadline {
+                thread::sleep(TICK / 10);
+            }
+            if web.is_finished() {
+                _ = web.join();
             }
-            _ => (),
         }
-    });
+    }
+}
 
-    rx
+/// Starts `command` with `sh -c` without waiting for it. Its output is discarded so that it can't
+/// garble the screen.
+fn run_hook(command: &str, env: &[(&str, &str)]) {
+    let child = process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        thread::spawn(move || child.wait());
+    }
 }
 
-fn display_forecast(conditions: &forecast::Results) -> Vec<Line> {
+fn display_forecast<'a>(
+    conditions: &'a forecast::Results,
+    units: Units,
+    theme: &Theme,
+) -> Vec<Line<'a>> {
     let mut spans = vec![Line::from("")];
 
     let name = if let Some(ref name) = conditions.name {
@@ -114,22 +1057,19 @@ fn display_forecast(conditions: &forecast::Results) -> Vec<Line> {
     };
     spans.push(Line::from(vec![
         Span::raw(" "),
-        Span::styled(
-            name,
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
+        Span::styled(name, theme.heading),
     ]));
 
-    let temp = if let Some(temp) = conditions.temperature {
-        format!("{temp:.1} F")
+    let (temp, temp_style) = if let Some(temp) = conditions.temperature {
+        let style = theme.temperature(temp);
+        let temp = units.fahrenheit(temp);
+        (format!("{temp:.1} {}", units.temperature_label()), style)
     } else {
-        MISSING.to_string()
+        (MISSING.to_string(), theme.value)
     };
     spans.push(Line::from(vec![
         Span::raw(format!(" {:13}", "Temperature")),
-        Span::styled(temp, Style::default().fg(Color::Green)),
+        Span::styled(temp, temp_style),
     ]));
 
     let text = if let Some(ref sf) = conditions.short_forecast {
@@ -139,106 +1079,106 @@ fn display_forecast(conditions: &forecast::Results) -> Vec<Line> {
     };
     spans.push(Line::from(vec![
         Span::raw(format!(" {:13}", "Conditions")),
-        Span::styled(text, Style::default().fg(Color::Green)),
+        Span::styled(text, theme.value),
     ]));
     spans
 }
 
-fn display_alert(alert: &alerts::Feature) -> Vec<Line> {
-    let onset: DateTime<Local> =
-        DateTime::from(DateTime::parse_from_rfc3339(&alert.properties.onset).unwrap());
-    let ends: DateTime<Local> =
-        DateTime::from(DateTime::parse_from_rfc3339(&alert.properties.ends).unwrap());
+fn display_alert<'a>(alert: &'a alerts::Feature, clock: &Clock, theme: &Theme) -> Vec<Line<'a>> {
+    let severity = theme.severity(alert.properties.severity_level());
+    let format = |timestamp: &str| {
+        clock
+            .parse(timestamp)
+            .map_or(MISSING.to_string(), |time| clock.when(&time))
+    };
     vec![
         Line::from(""),
         Line::from(vec![
             Span::raw(" "),
             Span::raw(format!("{:10}", "Event")),
-            Span::styled(&alert.properties.event, Style::default().fg(Color::Green)),
+            Span::styled(&alert.properties.event, severity),
         ]),
         Line::from(vec![
             Span::raw(" "),
             Span::raw(format!("{:10}", "Severity")),
-            Span::styled(
-                &alert.properties.severity,
-                Style::default().fg(Color::Green),
-            ),
+            Span::styled(&alert.properties.severity, severity),
         ]),
         Line::from(vec![
             Span::raw(" "),
             Span::raw(format!("{:10}", "Certainty")),
-            Span::styled(
-                &alert.properties.certainty,
-                Style::default().fg(Color::Green),
-            ),
+            Span::styled(&alert.properties.certainty, theme.value),
         ]),
         Line::from(vec![
             Span::raw(" "),
             Span::raw(format!("{:10}", "Onset")),
-            Span::styled(
-                format!("{}", onset.format("%d-%m-%Y %H:%M")),
-                Style::default().fg(Color::Green),
-            ),
+            Span::styled(format(&alert.properties.onset), theme.value),
         ]),
         Line::from(vec![
             Span::raw(" "),
             Span::raw(format!("{:10}", "Ends")),
-            Span::styled(
-                format!("{}", ends.format("%d-%m-%Y %H:%M")),
-                Style::default().fg(Color::Green),
-            ),
+            Span::styled(format(&alert.properties.ends), theme.value),
         ]),
     ]
 }
 
-fn display_current_conditions(current: &observation::Properties) -> Table {
+fn display_current_conditions<'a>(
+    current: &'a observation::Properties,
+    staleness: Option<String>,
+    focused: bool,
+    units: Units,
+    theme: &Theme,
+) -> Table<'a> {
+    let style = stale_style(staleness.is_some());
     let current_block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled(
-            " Current Conditions ",
-            Style::default().fg(Color::Yellow),
-        ))
+        .title(panel_title(" Current Conditions ", staleness, theme))
         .title_alignment(Alignment::Left)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(border_style(focused, theme))
         .border_type(BorderType::Rounded);
 
     let mut rows = vec![];
     rows.push(Row::new(vec![Cell::from("")]));
 
-    let temp = if let Some(temp) = current.temperature.value {
-        let temp = c2f(temp);
-        format!("{temp:.1} F")
-    } else {
-        MISSING.to_string()
+    // Values from a nearby station are followed by its identifier.
+    let cell = |text: String, style: Style, value: &observation::Value<Option<f32>>| {
+        let mut line = Line::from(text);
+        if let Some(span) = source_span(value, theme) {
+            line.push_span(span);
+        }
+        Cell::from(line).style(style)
+    };
+
+    // Temperatures are coloured by how warm they are.
+    let temperature = |temp: &observation::Value<Option<f32>>| match temp.value {
+        Some(value) => cell(
+            format!("{:.1} {}", units.celsius(value), units.temperature_label()),
+            theme.temperature(temperature::c2f(value)),
+            temp,
+        ),
+        None => Cell::from(MISSING).style(theme.value),
     };
     rows.push(Row::new(vec![
         Cell::from(" Temperature"),
-        Cell::from(temp).style(Style::default().fg(Color::Green)),
+        temperature(&current.temperature),
     ]));
 
     let wind = if let (Some(speed), Some(dir)) =
         (current.wind_speed.value, current.wind_direction.value)
     {
-        let speed = kph2mph(speed);
+        let speed = units.kph(speed);
         let compass = degree_to_compass(dir);
-        format!("{speed:.1} MPH ({compass})")
+        format!("{speed:.1} {} ({compass})", units.speed_label())
     } else {
         MISSING.to_string()
     };
     rows.push(Row::new(vec![
         Cell::from(" Wind"),
-        Cell::from(wind).style(Style::default().fg(Color::Green)),
+        cell(wind, theme.value, &current.wind_speed),
     ]));
 
-    let wind_chill = if let Some(wind_chill) = current.wind_chill.value {
-        let wind_chill = c2f(wind_chill);
-        format!("{wind_chill:.1} F")
-    } else {
-        MISSING.to_string()
-    };
     rows.push(Row::new(vec![
         Cell::from(" Wind Chill"),
-        Cell::from(wind_chill).style(Style::default().fg(Color::Green)),
+        temperature(&current.wind_chill),
     ]));
 
     let humid = if let Some(humid) = current.relative_humidity.value {
@@ -248,7 +1188,7 @@ fn display_current_conditions(current: &observation::Properties) -> Table {
     };
     rows.push(Row::new(vec![
         Cell::from(" Humidity"),
-        Cell::from(humid).style(Style::default().fg(Color::Green)),
+        cell(humid, theme.value, &current.relative_humidity),
     ]));
 
     let text = if current.description.is_empty() {
@@ -258,36 +1198,287 @@ fn display_current_conditions(current: &observation::Properties) -> Table {
     };
     rows.push(Row::new(vec![
         Cell::from(" Conditions"),
-        Cell::from(text).style(Style::default().fg(Color::Green)),
+        Cell::from(text).style(theme.value),
     ]));
 
-    Table::new(rows, [Constraint::Length(12), Constraint::Length(25)]).block(current_block)
+    Table::new(rows, [Constraint::Length(12), Constraint::Length(25)])
+        .block(current_block)
+        .style(style)
 }
 
-fn display_headline<'a>(
-    station: &'a station::Properties,
-    observation: &'a observation::Properties,
-) -> Paragraph<'a> {
-    let date: DateTime<Local> =
-        DateTime::from(DateTime::parse_from_rfc3339(&observation.timestamp).unwrap());
-    Paragraph::new(vec![
-        Line::from(vec![
-            Span::raw(" "),
-            Span::styled(
-                station.station_identifier.clone(),
-                Style::default().fg(Color::Blue),
-            ),
+/// The nearby station a value was filled in from, to follow the value.
+fn source_span(value: &observation::Value<Option<f32>>, theme: &Theme) -> Option<Span<'static>> {
+    value
+        .source
+        .as_ref()
+        .map(|id| Span::styled(format!(" {id}"), theme.station))
+}
+
+/// The station and observation time on the left, and on the right, room permitting, when the
+/// next refresh is due and when each resource was last fetched.
+fn display_headline(f: &mut Frame, area: Rect, state: &State) {
+    let stale = state.station.is_stale() || state.observation.is_stale();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(state.theme.border)
+        .border_type(BorderType::Rounded)
+        .style(stale_style(stale));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::vertical([Constraint::Length(1); 2]).split(inner);
+    for ((left, right), &row) in headline_lines(state)
+        .into_iter()
+        .zip(freshness_lines(state))
+        .zip(rows.iter())
+    {
+        let fits = left.width() + right.width() + 2 <= row.width as usize;
+        f.render_widget(left, row);
+        if fits {
+            f.render_widget(right.right_aligned(), row);
+        }
+    }
+}
+
+/// The station's name, and when the observation was made and how long ago.
+fn headline_lines(state: &State) -> [Line<'_>; 2] {
+    let theme = &state.theme;
+    let (id, name) = match state.station.value {
+        Some(ref station) => (
+            station.properties.station_identifier.clone(),
+            station.properties.name.clone(),
+        ),
+        None => (MISSING.to_string(), MISSING.to_string()),
+    };
+    let observed = state
+        .observation
+        .value
+        .as_ref()
+        .and_then(|obs| state.clock.parse(&obs.properties.timestamp));
+    let mut date = vec![Span::raw(format!(
+        " {}",
+        observed.map_or(MISSING.to_string(), |date| state.clock.date_time(&date))
+    ))];
+    if let Some(observed) = observed {
+        let age = state.clock.now() - observed.to_utc();
+        let style = if age.num_minutes()
+            > i64::try_from(state.config.display.max_age).unwrap_or(i64::MAX)
+        {
+            theme.warning
+        } else {
+            Style::default()
+        };
+        date.push(Span::raw(" · "));
+        date.push(Span::styled(format_relative(age), style));
+    }
+    [
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled(id, theme.station),
             Span::raw(" : "),
-            Span::styled(station.name.clone(), Style::default().fg(Color::Yellow)),
+            Span::styled(name, theme.title),
         ]),
-        Line::from(format!(" {}", date.format("%d-%m-%Y %H:%M"))),
-    ])
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan))
-            .border_type(BorderType::Rounded),
-    )
+        Line::from(date),
+    ]
+}
+
+/// When the next refresh is due, and when each resource last arrived.
+fn freshness_lines(state: &State) -> [Line<'_>; 2] {
+    let now = state.clock.now();
+    let countdown = match state.next_refresh {
+        Some(next) if next > now => Line::from(vec![
+            Span::raw("Next refresh in "),
+            Span::styled(format_age(next - now), state.theme.value),
+            Span::raw(" "),
+        ]),
+        Some(_) => Line::from("Refreshing… "),
+        None => Line::default(),
+    };
+
+    let mut fetched = vec![Span::raw("Fetched")];
+    for (name, time) in [
+        ("obs", state.observation.fetched_at),
+        ("alerts", state.alerts.fetched_at),
+        ("forecast", state.forecast.fetched_at),
+        ("hourly", state.hourly_forecast.fetched_at),
+    ] {
+        let time = time.map_or(MISSING.to_string(), |time| state.clock.time(&time));
+        fetched.push(Span::raw(format!(" {name} ")));
+        fetched.push(Span::styled(time, state.theme.value));
+    }
+    fetched.push(Span::raw(" "));
+    [countdown, Line::from(fetched)]
+}
+
+/// Formats how long ago something happened, e.g. "just now", "12 min ago" or "3 h 5 min ago".
+fn format_relative(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    match minutes {
+        0 => "just now".to_string(),
+        1..60 => format!("{minutes} min ago"),
+        60..1440 => format!("{} h {} min ago", minutes / 60, minutes % 60),
+        _ => format!("{} days ago", minutes / 1440),
+    }
+}
+
+/// Formats a duration as e.g. "45s", "12m" or "1h 12m".
+fn format_age(age: chrono::Duration) -> String {
+    let secs = age.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// Stale values are drawn dimmed.
+fn stale_style(stale: bool) -> Style {
+    if stale {
+        Style::default().add_modifier(Modifier::DIM)
+    } else {
+        Style::default()
+    }
+}
+
+fn panel_title<'a>(name: &'a str, staleness: Option<String>, theme: &Theme) -> Line<'a> {
+    let mut spans = vec![Span::styled(name, theme.title)];
+    if let Some(staleness) = staleness {
+        spans.push(Span::styled(format!("· {staleness} "), theme.error));
+    }
+    Line::from(spans)
+}
+
+fn display_error<'a>(err: &'a noaa::Error, theme: &Theme) -> Vec<Line<'a>> {
+    let label = |name: &str| Span::raw(format!(" {name:15}"));
+    let value = |text: String| Span::styled(text, theme.error);
+
+    let mut lines = vec![Line::from("")];
+    match err {
+        noaa::Error::Network(err) => {
+            lines.push(Line::from(vec![label("Network"), value(err.to_string())]));
+        }
+        noaa::Error::Timeout(err) => {
+            let url = err.url().map_or(MISSING.to_string(), |url| url.to_string());
+            lines.push(Line::from(vec![label("Timeout"), value(url)]));
+        }
+        noaa::Error::Status { url, status } => {
+            lines.push(Line::from(vec![label("Status"), value(status.to_string())]));
+            lines.push(Line::from(vec![label("URL"), value(url.clone())]));
+        }
+        noaa::Error::Problem {
+            url,
+            status,
+            problem,
+        } => {
+            let field = |text: &Option<String>| text.clone().unwrap_or(MISSING.to_string());
+            lines.push(Line::from(vec![label("Status"), value(status.to_string())]));
+            lines.push(Line::from(vec![
+                label("Title"),
+                value(field(&problem.title)),
+            ]));
+            lines.push(Line::from(vec![
+                label("Detail"),
+                value(field(&problem.detail)),
+            ]));
+            lines.push(Line::from(vec![
+                label("Correlation ID"),
+                value(field(&problem.correlation_id)),
+            ]));
+            lines.push(Line::from(vec![label("URL"), value(url.clone())]));
+        }
+        noaa::Error::NotRecorded { url } => {
+            lines.push(Line::from(vec![label("Not recorded"), value(url.clone())]));
+        }
+        noaa::Error::CircuitOpen { retry_in } => {
+            lines.push(Line::from(vec![
+                label("Paused"),
+                value(format!("next attempt in {}s", retry_in.as_secs())),
+            ]));
+        }
+        noaa::Error::Decode { url, path, source } => {
+            lines.push(Line::from(vec![label("Decode"), value(source.to_string())]));
+            lines.push(Line::from(vec![label("Path"), value(path.clone())]));
+            lines.push(Line::from(vec![label("URL"), value(url.clone())]));
+        }
+    }
+    lines
+}
+
+fn error_panel(f: &mut Frame, station: &str, err: &noaa::Error, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(
+            format!(" Unable to fetch data for {station} "),
+            theme.title,
+        ))
+        .title_alignment(Alignment::Left)
+        .border_style(theme.error)
+        .border_type(BorderType::Rounded);
+    let widget = Paragraph::new(display_error(err, theme))
+        .wrap(Wrap { trim: false })
+        .block(block);
+    let area = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0)])
+        .split(f.area());
+    f.render_widget(widget, area[0]);
+}
+
+fn display_status<'a>(stats: &'a noaa::Stats, clock: &Clock, theme: &Theme) -> Line<'a> {
+    let mut spans = vec![
+        Span::raw(" Retries "),
+        Span::styled(stats.retries.to_string(), theme.value),
+    ];
+
+    if let Some((instant, ref err)) = stats.last_error {
+        spans.push(Span::raw("  Last error "));
+        spans.push(Span::styled(
+            format!(
+                "{} {err}",
+                clock.local(&clock.at(instant)).format("%H:%M:%S")
+            ),
+            theme.error,
+        ));
+    }
+
+    if let Some(until) = stats.open_until {
+        let now = Instant::now();
+        if until > now {
+            spans.push(Span::raw("  Paused "));
+            spans.push(Span::styled(
+                format!("{}s", (until - now).as_secs()),
+                theme.warning,
+            ));
+        }
+    }
+
+    Line::from(spans)
+}
+
+/// Draws a banner along the top edge of the screen while showing saved data.
+fn offline_banner(f: &mut Frame, time: &str, theme: &Theme) {
+    let area = f.area();
+    if area.height == 0 {
+        return;
+    }
+    let line = Rect::new(area.x, area.y, area.width, 1);
+    let text = format!(" OFFLINE — data from {time} ");
+    let banner = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(theme.banner);
+    f.render_widget(banner, line);
+}
+
+/// Draws the status line along the bottom edge of the screen.
+fn status_line(f: &mut Frame, stats: &noaa::Stats, clock: &Clock, theme: &Theme) {
+    let area = f.area();
+    if area.height == 0 {
+        return;
+    }
+    let line = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+    f.render_widget(Paragraph::new(display_status(stats, clock, theme)), line);
 }
 
 fn loading(f: &mut Frame, idx: usize) {
@@ -317,71 +1508,1554 @@ fn loading(f: &mut Frame, idx: usize) {
     f.render_widget(widget, horiz_layout[1]);
 }
 
-fn ui(
-    f: &mut Frame,
-    current: &observation::Observation,
-    station: &station::Station,
-    alerts: &alerts::Alerts,
-    forecast: &forecast::Forecast,
-) {
-    let vert_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(4),
-            Constraint::Length(f.area().height - 4),
-        ])
-        .split(f.area());
+/// Below this the dashboard is replaced by a message asking for a bigger terminal.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 12;
 
-    let title_widget = display_headline(&station.properties, &current.properties);
-    f.render_widget(title_widget, vert_layout[0]);
+/// Terminals narrower than this get a single stacked column.
+const NARROW: u16 = 80;
 
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(60), Constraint::Percentage(100)].as_ref())
-        .split(vert_layout[1]);
+/// Terminals at least this wide get a third column with hourly charts.
+const WIDE: u16 = 150;
 
-    let lchunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunks[0]);
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Breakpoint {
+    /// Everything stacked in one column.
+    Narrow,
+
+    /// Current conditions and alerts beside the forecast.
+    Medium,
+
+    /// As `Medium`, plus hourly charts.
+    Wide,
+}
+
+impl Breakpoint {
+    /// The layout for an area, or `None` if it is too small to draw the dashboard in.
+    fn of(area: Rect) -> Option<Self> {
+        match area.width {
+            _ if area.width < MIN_WIDTH || area.height < MIN_HEIGHT => None,
+            ..NARROW => Some(Breakpoint::Narrow),
+            NARROW..WIDE => Some(Breakpoint::Medium),
+            _ => Some(Breakpoint::Wide),
+        }
+    }
+}
+
+fn too_small(f: &mut Frame, theme: &Theme) {
+    let area = f.area();
+    let text = vec![
+        Line::from("Terminal too small"),
+        Line::from(format!(
+            "{}x{}, need {MIN_WIDTH}x{MIN_HEIGHT}",
+            area.width, area.height
+        )),
+    ];
+    let [middle] = Layout::vertical([Constraint::Length(2)])
+        .flex(Flex::Center)
+        .areas(area);
+    let widget = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(theme.warning);
+    f.render_widget(widget, middle);
+}
+
+fn ui(f: &mut Frame, state: &State, view: &mut View, breakpoint: Breakpoint) {
+    let [headline, tabs, body] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .margin(1)
+    .areas(f.area());
+
+    display_headline(f, headline, state);
+    display_tabs(f, tabs, view, &state.theme);
+
+    let default = default_layout(breakpoint);
+    screen(f, body, state, view, &default);
+}
+
+/// The dashboard drawn below the prompt with `--inline`: the station on one line above the
+/// panels, without the tabs or the status line.
+fn inline_ui(f: &mut Frame, station: &str, state: &State, view: &mut View) {
+    view.targets.clear();
+    if let Some(err) = state.error().filter(|_| !state.has_data()) {
+        error_panel(f, station, err, &state.theme);
+        return;
+    }
+
+    let [headline, body] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(f.area());
+    let [mut line, date] = headline_lines(state);
+    line.spans.push(Span::raw("  "));
+    line.spans.extend(date.spans);
+    let stale = state.station.is_stale() || state.observation.is_stale();
+    f.render_widget(Paragraph::new(line).style(stale_style(stale)), headline);
+
+    let default = inline_layout(body.width);
+    screen(f, body, state, view, &default);
+    if let Some(time) = state.offline {
+        offline_banner(f, &state.clock.date_time(&time), &state.theme);
+    }
+}
+
+/// Draws the screen being viewed in `area`, laid out by `default` unless the config file says
+/// otherwise, and any popup over the whole frame.
+fn screen(f: &mut Frame, area: Rect, state: &State, view: &mut View, default: &Node) {
+    let layout = state.config.layout.as_ref().unwrap_or(default);
+    let widgets = match view.screen {
+        Screen::Dashboard => layout.split(area),
+        Screen::Hourly => vec![(Widget::Hourly, area)],
+    };
+    for (widget, area) in widgets {
+        match widget {
+            Widget::Current => current_panel(f, area, state, view),
+            Widget::Alerts => alerts_panel(f, area, state, view),
+            Widget::Forecast => forecast_panel(f, area, state, view),
+            Widget::Hourly => display_hourly(f, area, state),
+            Widget::Temperature => temperature_panel(f, area, state),
+            Widget::Wind => wind_panel(f, area, state),
+            Widget::Pressure => pressure_panel(f, area, state),
+            Widget::Astronomy => astronomy_panel(f, area, state),
+        }
+    }
+
+    if view.details {
+        details_popup(f, state, view);
+    }
+    if view.info {
+        station_popup(f, state);
+    }
+    if view.help {
+        help_popup(f, state);
+    }
+}
+
+/// The inline dashboard when the config file doesn't lay it out: one row of panels, fewer when
+/// narrow.
+fn inline_layout(width: u16) -> Node {
+    let widget = |widget| Node::widget(widget, Size::Fill(1));
+    let widgets = match width {
+        ..NARROW => vec![widget(Widget::Current)],
+        NARROW..WIDE => vec![widget(Widget::Current), widget(Widget::Forecast)],
+        _ => vec![
+            widget(Widget::Current),
+            widget(Widget::Alerts),
+            widget(Widget::Forecast),
+        ],
+    };
+    Node::columns(Size::Fill(1), widgets)
+}
+
+/// The dashboard when the config file doesn't lay it out.
+fn default_layout(breakpoint: Breakpoint) -> Node {
+    let widget = Node::widget;
+    match breakpoint {
+        // An alert is eight rows with its borders, and is not drawn at all in fewer.
+        Breakpoint::Narrow => Node::rows(
+            Size::Fill(1),
+            vec![
+                widget(Widget::Current, Size::Length(8)),
+                widget(Widget::Alerts, Size::Min(8)),
+                widget(Widget::Forecast, Size::Fill(1)),
+            ],
+        ),
+        Breakpoint::Medium => Node::columns(
+            Size::Fill(1),
+            vec![
+                Node::rows(
+                    Size::Percentage(50),
+                    vec![
+                        widget(Widget::Current, Size::Percentage(50)),
+                        widget(Widget::Alerts, Size::Percentage(50)),
+                    ],
+                ),
+                widget(Widget::Forecast, Size::Percentage(50)),
+            ],
+        ),
+        Breakpoint::Wide => Node::columns(
+            Size::Fill(1),
+            vec![
+                Node::rows(
+                    Size::Percentage(34),
+                    vec![
+                        widget(Widget::Current, Size::Percentage(50)),
+                        widget(Widget::Alerts, Size::Percentage(50)),
+                    ],
+                ),
+                widget(Widget::Forecast, Size::Percentage(33)),
+                widget(Widget::Hourly, Size::Percentage(33)),
+            ],
+        ),
+    }
+}
 
-    let current_conditions = display_current_conditions(&current.properties);
-    f.render_widget(current_conditions, lchunks[0]);
+/// How out of date the latest observation is, if it is.
+fn observation_staleness(state: &State) -> Option<String> {
+    let observed = state.observation.value.as_ref().and_then(|obs| {
+        DateTime::parse_from_rfc3339(&obs.properties.timestamp)
+            .ok()
+            .map(|time| time.to_utc())
+    });
+    state
+        .observation
+        .staleness("observed", observed, state.clock.now())
+}
 
+/// A bordered panel, dimmed along with its contents while stale.
+fn panel<'a>(title: &'a str, staleness: Option<String>, focused: bool, theme: &Theme) -> Block<'a> {
+    let style = stale_style(staleness.is_some());
+    Block::default()
+        .borders(Borders::ALL)
+        .title(panel_title(title, staleness, theme))
+        .title_alignment(Alignment::Left)
+        .border_style(border_style(focused, theme))
+        .border_type(BorderType::Rounded)
+        .style(style)
+}
+
+fn current_panel(f: &mut Frame, area: Rect, state: &State, view: &mut View) {
+    let empty = observation::Properties::empty(&state.clock);
+    let current = state
+        .observation
+        .value
+        .as_ref()
+        .map_or(&empty, |obs| &obs.properties);
+    let current_conditions = display_current_conditions(
+        current,
+        observation_staleness(state),
+        view.focus == Panel::Current,
+        state.config.units,
+        &state.theme,
+    );
+    f.render_widget(current_conditions, area);
+    view.targets.push((area, Target::Panel(Panel::Current)));
+}
+
+fn alerts_panel(f: &mut Frame, area: Rect, state: &State, view: &mut View) {
+    let staleness = state.alerts.staleness("fetched", None, state.clock.now());
+    let alert_style = stale_style(staleness.is_some());
     let alert_block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled(" Alerts ", Style::default().fg(Color::Yellow)))
+        .title(panel_title(" Alerts ", staleness, &state.theme))
         .title_alignment(Alignment::Left)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(border_style(view.focus == Panel::Alerts, &state.theme))
         .border_type(BorderType::Rounded);
 
+    let alerts_inner = alert_block.inner(area);
+
     let mut list_items = vec![];
-    if alerts.features.is_empty() {
-        list_items.push(ListItem::new(format!("\n  {MISSING}")));
-    } else {
-        for alert in &alerts.features {
-            list_items.push(ListItem::new(display_alert(alert)));
+    match state.alerts.value {
+        Some(ref alerts) if !alerts.features.is_empty() => {
+            for alert in &alerts.features {
+                list_items.push(ListItem::new(display_alert(
+                    alert,
+                    &state.clock,
+                    &state.theme,
+                )));
+            }
         }
+        _ => list_items.push(ListItem::new(format!("\n  {MISSING}"))),
+    }
+    let heights: Vec<_> = list_items.iter().map(ListItem::height).collect();
+    let alert_list = List::new(list_items)
+        .block(alert_block)
+        .style(alert_style)
+        .highlight_style(highlight_style(view.focus == Panel::Alerts));
+    f.render_stateful_widget(alert_list, area, &mut view.alerts);
+    view.targets.push((area, Target::Panel(Panel::Alerts)));
+    if state
+        .alerts
+        .value
+        .as_ref()
+        .is_some_and(|alerts| !alerts.features.is_empty())
+    {
+        record_items(view, Panel::Alerts, alerts_inner, &heights);
     }
-    let alert_list = List::new(list_items).block(alert_block);
-    f.render_widget(alert_list, lchunks[1]);
+}
 
+fn forecast_panel(f: &mut Frame, area: Rect, state: &State, view: &mut View) {
+    let staleness = state.forecast.staleness("fetched", None, state.clock.now());
+    let forecast_style = stale_style(staleness.is_some());
     let forecast_block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled(
-            " Forecast ",
-            Style::default().fg(Color::Yellow),
-        ))
+        .title(panel_title(" Forecast ", staleness, &state.theme))
         .title_alignment(Alignment::Left)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(border_style(view.focus == Panel::Forecast, &state.theme))
         .border_type(BorderType::Rounded);
 
+    let forecast_inner = forecast_block.inner(area);
+
     let mut list_items = vec![];
-    for fc in &forecast.properties.periods {
-        list_items.push(ListItem::new(display_forecast(fc)));
+    if let Some(ref forecast) = state.forecast.value {
+        for fc in &forecast.properties.periods {
+            list_items.push(ListItem::new(display_forecast(
+                fc,
+                state.config.units,
+                &state.theme,
+            )));
+        }
+    }
+    let heights: Vec<_> = list_items.iter().map(ListItem::height).collect();
+    let list = List::new(list_items)
+        .block(forecast_block)
+        .style(forecast_style)
+        .highlight_style(highlight_style(view.focus == Panel::Forecast));
+    f.render_stateful_widget(list, area, &mut view.forecast);
+    view.targets.push((area, Target::Panel(Panel::Forecast)));
+    record_items(view, Panel::Forecast, forecast_inner, &heights);
+}
+
+/// Rows of a label and a value, as in the current conditions.
+fn label_lines<'a>(rows: Vec<(&'a str, String)>, style: Style) -> Vec<Line<'a>> {
+    let mut lines = vec![Line::from("")];
+    for (label, value) in rows {
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {label:12}")),
+            Span::styled(value, style),
+        ]));
+    }
+    lines
+}
+
+/// The current wind, above the hourly forecast's wind speeds.
+fn wind_panel(f: &mut Frame, area: Rect, state: &State) {
+    let units = state.config.units;
+    let theme = &state.theme;
+    let block = panel(" Wind ", observation_staleness(state), false, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let current = state.observation.value.as_ref().map(|obs| &obs.properties);
+    let speed = |speed: Option<f32>| {
+        speed.map_or(MISSING.to_string(), |speed| {
+            format!("{:.1} {}", units.kph(speed), units.speed_label())
+        })
+    };
+    let direction = current
+        .and_then(|current| current.wind_direction.value)
+        .map_or(MISSING.to_string(), |dir| {
+            format!("{} ({dir:.0}°)", degree_to_compass(dir))
+        });
+    let rows = vec![
+        ("Speed", speed(current.and_then(|c| c.wind_speed.value))),
+        ("Gusts", speed(current.and_then(|c| c.wind_gust.value))),
+        ("Direction", direction),
+    ];
+    let mut lines = label_lines(rows, theme.value);
+    if let Some(span) = current.and_then(|current| source_span(&current.wind_speed, theme)) {
+        lines[1].push_span(span.clone());
+        lines[3].push_span(span);
+    }
+    let [text_area, chart_area] =
+        Layout::vertical([Constraint::Length(5), Constraint::Fill(1)]).areas(inner);
+    f.render_widget(Paragraph::new(lines), text_area);
+
+    if let Some(periods) = hourly_periods(state) {
+        let title = format!(" Hourly ({})", units.speed_label().to_lowercase());
+        f.render_widget(wind_chart(state, periods, title), chart_area);
+    }
+}
+
+/// How much pressure history to keep for the chart.
+const PRESSURE_HISTORY: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// How far back the pressure trend looks.
+const PRESSURE_TENDENCY: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// Changes in pressure smaller than this, in pascals, count as steady.
+const PRESSURE_STEADY: f32 = 50.0;
+
+/// Barometric pressure and how it has changed over the last few hours.
+fn pressure_panel(f: &mut Frame, area: Rect, state: &State) {
+    let units = state.config.units;
+    let theme = &state.theme;
+    let block = panel(" Pressure ", observation_staleness(state), false, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let decimals = match units {
+        Units::Imperial => 2,
+        Units::Metric => 1,
+    };
+    let pressure = |pa: f32| {
+        format!(
+            "{:.decimals$} {}",
+            units.pascals(pa),
+            units.pressure_label()
+        )
+    };
+    let current = state
+        .observation
+        .value
+        .as_ref()
+        .map(|obs| &obs.properties.barometric_pressure);
+    let trend = match pressure_tendency(&state.pressure) {
+        Some((change, _)) if change.abs() < PRESSURE_STEADY => "→ Steady".to_string(),
+        Some((change, since)) => format!(
+            "{} {}{} in {}",
+            if change > 0.0 {
+                "↑ Rising"
+            } else {
+                "↓ Falling"
+            },
+            if change > 0.0 { "+" } else { "-" },
+            pressure(change.abs()),
+            format_age(since),
+        ),
+        None => MISSING.to_string(),
+    };
+    let rows = vec![
+        (
+            "Pressure",
+            current
+                .and_then(|current| current.value)
+                .map_or(MISSING.to_string(), pressure),
+        ),
+        ("Trend", trend),
+    ];
+    let mut lines = label_lines(rows, theme.value);
+    if let Some(span) = current.and_then(|current| source_span(current, theme)) {
+        lines[1].push_span(span);
+    }
+    let [text_area, chart_area] =
+        Layout::vertical([Constraint::Length(4), Constraint::Fill(1)]).areas(inner);
+    f.render_widget(Paragraph::new(lines), text_area);
+
+    if state.pressure.len() > 1 {
+        let low = state
+            .pressure
+            .iter()
+            .map(|&(_, pa)| pa)
+            .fold(f32::MAX, f32::min);
+        let data: Vec<u64> = state
+            .pressure
+            .iter()
+            .map(|&(_, pa)| (pa - low) as u64 + 1)
+            .collect();
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .style(theme.value)
+            .block(Block::default().padding(Padding::horizontal(1)));
+        f.render_widget(sparkline, chart_area);
     }
-    let list = List::new(list_items).block(forecast_block);
+}
+
+/// The change in pressure over the last [`PRESSURE_TENDENCY`], or as much of it as there are
+/// readings for, and how long that was.
+fn pressure_tendency(readings: &[(DateTime<Utc>, f32)]) -> Option<(f32, chrono::Duration)> {
+    let &(latest, now) = readings.last()?;
+    let since = latest - chrono::Duration::from_std(PRESSURE_TENDENCY).unwrap();
+    let &(then, before) = readings
+        .iter()
+        .find(|&&(time, _)| time >= since)
+        .filter(|&&(time, _)| time < latest)?;
+    Some((now - before, latest - then))
+}
+
+/// Sunrise, sunset and the moon at the station.
+fn astronomy_panel(f: &mut Frame, area: Rect, state: &State) {
+    let theme = &state.theme;
+    let block = panel(" Sun & Moon ", None, false, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let now = state.clock.now();
+    let time = |time: DateTime<Utc>| state.clock.time(&time);
+    let sun = state.station.value.as_ref().map(|station| {
+        let (lat, lon) = station.coordinates();
+        let today = state.clock.local(&now).date_naive();
+        Sun::on(today, lat.into(), lon.into())
+    });
+    let (rise, set) = match sun {
+        Some(Sun::RiseSet(rise, set)) => (time(rise), time(set)),
+        Some(Sun::AlwaysUp) => ("Up all day".to_string(), MISSING.to_string()),
+        Some(Sun::AlwaysDown) => ("Down all day".to_string(), MISSING.to_string()),
+        None => (MISSING.to_string(), MISSING.to_string()),
+    };
+    let daylight = sun.map_or(MISSING.to_string(), |sun| {
+        let minutes = sun.daylight().num_minutes();
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    });
+    let moon = Moon::at(now);
+    let rows = vec![
+        ("Sunrise", rise),
+        ("Sunset", set),
+        ("Daylight", daylight),
+        ("Moon", moon.name().to_string()),
+        (
+            "Illuminated",
+            format!("{:.0}%", moon.illumination() * 100.0),
+        ),
+    ];
+    f.render_widget(Paragraph::new(label_lines(rows, theme.value)), inner);
+}
+
+/// Draws the screen names, highlighting the current one, and records where they are.
+fn display_tabs(f: &mut Frame, area: Rect, view: &mut View, theme: &Theme) {
+    let mut spans = vec![Span::raw(" ")];
+    let mut x = area.x + 1;
+    for (i, screen) in Screen::ALL.into_iter().enumerate() {
+        let label = format!(" {} {} ", i + 1, screen.title());
+        let width = label.chars().count() as u16;
+        let style = if screen == view.screen {
+            theme.title.add_modifier(Modifier::REVERSED)
+        } else {
+            theme.title
+        };
+        spans.push(Span::styled(label, style));
+        spans.push(Span::raw(" "));
+        view.targets.push((
+            Rect::new(x, area.y, width, 1).intersection(area),
+            Target::Screen(screen),
+        ));
+        x += width + 1;
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Records where the visible items of a list were drawn, given the height of every item.
+fn record_items(view: &mut View, panel: Panel, inner: Rect, heights: &[usize]) {
+    let offset = match panel {
+        Panel::Alerts => view.alerts.offset(),
+        Panel::Forecast => view.forecast.offset(),
+        Panel::Current => return,
+    };
+    let mut y = inner.y;
+    for (i, &height) in heights.iter().enumerate().skip(offset) {
+        if y >= inner.bottom() {
+            break;
+        }
+        let area = Rect::new(inner.x, y, inner.width, height as u16).intersection(inner);
+        view.targets.push((area, Target::Item(panel, i)));
+        y += height as u16;
+    }
+}
+
+/// The focused panel has a brighter border.
+fn border_style(focused: bool, theme: &Theme) -> Style {
+    if focused {
+        theme.focused
+    } else {
+        theme.border
+    }
+}
+
+/// The selected item is only marked in the focused list.
+fn highlight_style(focused: bool) -> Style {
+    if focused {
+        Style::default().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default()
+    }
+}
+
+/// A bordered box in the middle of the screen, cleared of whatever was drawn beneath it.
+fn popup<'a>(
+    f: &mut Frame,
+    title: &'a str,
+    width: u16,
+    height: u16,
+    theme: &Theme,
+) -> (Block<'a>, Rect) {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(f.area());
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, theme.title))
+        .title_alignment(Alignment::Left)
+        .border_style(theme.focused)
+        .border_type(BorderType::Rounded);
+    (block, area)
+}
+
+/// The full text of the selected alert or forecast period.
+fn details_popup(f: &mut Frame, state: &State, view: &View) {
+    let theme = &state.theme;
+    let text_style = theme.value;
+    let paragraph = |text: &Option<String>| {
+        let text = text.clone().unwrap_or(MISSING.to_string());
+        text.lines()
+            .map(|line| Line::styled(line.to_owned(), text_style))
+            .collect::<Vec<_>>()
+    };
+
+    let (title, mut lines) = match view.focus {
+        Panel::Alerts => {
+            let Some(alert) = view.alerts.selected().and_then(|i| {
+                state
+                    .alerts
+                    .value
+                    .as_ref()
+                    .and_then(|alerts| alerts.features.get(i))
+            }) else {
+                return;
+            };
+            let properties = &alert.properties;
+            let mut lines = paragraph(&properties.headline);
+            lines.push(Line::from(""));
+            lines.extend(paragraph(&properties.description));
+            if properties.instruction.is_some() {
+                lines.push(Line::from(""));
+                lines.extend(paragraph(&properties.instruction));
+            }
+            (format!(" {} ", properties.event), lines)
+        }
+        Panel::Forecast => {
+            let Some(period) = view.forecast.selected().and_then(|i| {
+                state
+                    .forecast
+                    .value
+                    .as_ref()
+                    .and_then(|forecast| forecast.properties.periods.get(i))
+            }) else {
+                return;
+            };
+            let text = period
+                .detailed_forecast
+                .clone()
+                .or(period.short_forecast.clone());
+            let lines = paragraph(&text);
+            let name = period.name.clone().unwrap_or(MISSING.to_string());
+            (format!(" {name} "), lines)
+        }
+        Panel::Current => return,
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "Esc to close",
+        Style::default().add_modifier(Modifier::DIM),
+    ));
+
+    let area = f.area();
+    let width = (area.width * 3 / 4).max(MIN_WIDTH).min(area.width);
+    let height = (area.height * 2 / 3).max(MIN_HEIGHT).min(area.height);
+    let (block, area) = popup(f, &title, width, height, theme);
+    let widget = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(block.padding(Padding::new(1, 1, 1, 0)));
+    f.render_widget(widget, area);
+}
+
+/// Everything known about the station, to check it is the right one.
+fn station_popup(f: &mut Frame, state: &State) {
+    let theme = &state.theme;
+    let Some(ref station) = state.station.value else {
+        return;
+    };
+    let properties = &station.properties;
+    let (lat, lon) = station.coordinates();
+    let hemisphere = |value: f32, positive, negative| {
+        let side = if value < 0.0 { negative } else { positive };
+        format!("{:.4}° {side}", value.abs())
+    };
+    let units = state.config.units;
+    let zone = |url: &Option<String>| {
+        url.as_deref()
+            .map_or(MISSING, station::zone_name)
+            .to_string()
+    };
+    let rows = [
+        ("Station", properties.station_identifier.clone()),
+        ("Name", properties.name.clone()),
+        (
+            "Coordinates",
+            format!(
+                "{}, {}",
+                hemisphere(lat, "N", "S"),
+                hemisphere(lon, "E", "W")
+            ),
+        ),
+        (
+            "Elevation",
+            properties.elevation.value.map_or(MISSING.to_string(), |m| {
+                format!("{:.0} {}", units.metres(m), units.height_label())
+            }),
+        ),
+        (
+            "Time zone",
+            properties.time_zone.clone().unwrap_or(MISSING.to_string()),
+        ),
+        ("Forecast zone", station.zone_id().to_string()),
+        ("County", zone(&properties.county)),
+        ("Fire zone", zone(&properties.fire_weather_zone)),
+        (
+            "Office",
+            properties
+                .grid
+                .as_ref()
+                .map_or(MISSING.to_string(), |grid| grid.office.clone()),
+        ),
+        (
+            "Gridpoint",
+            properties
+                .grid
+                .as_ref()
+                .map_or(MISSING.to_string(), |grid| {
+                    format!("{}, {}", grid.x, grid.y)
+                }),
+        ),
+    ];
+
+    let width = rows
+        .iter()
+        .map(|(_, value)| value.chars().count() as u16)
+        .max()
+        .unwrap_or(0)
+        + 21;
+    let height = rows.len() as u16 + 4;
+    let rows = rows.into_iter().map(|(label, value)| {
+        Row::new(vec![
+            Cell::from(format!(" {label}")),
+            Cell::from(value).style(theme.value),
+        ])
+    });
+    let area = f.area();
+    let (block, area) = popup(
+        f,
+        " Station ",
+        width.min(area.width),
+        height.min(area.height),
+        theme,
+    );
+    let table = Table::new(rows, [Constraint::Length(16), Constraint::Fill(1)])
+        .block(block.padding(Padding::vertical(1)));
+    f.render_widget(table, area);
+}
+
+/// Lists the key bindings.
+fn help_popup(f: &mut Frame, state: &State) {
+    let theme = &state.theme;
+    let mut help = state.keymap.help();
+    help.push((MOUSE_HELP.0.to_string(), MOUSE_HELP.1));
+    let keys_width = help
+        .iter()
+        .map(|(keys, _)| keys.chars().count() as u16)
+        .max()
+        .unwrap_or(0)
+        + 3;
+    let width = help
+        .iter()
+        .map(|(_, action)| action.chars().count() as u16)
+        .max()
+        .unwrap_or(0)
+        + keys_width
+        + 4;
+    let height = help.len() as u16 + 4;
+    let rows = help.into_iter().map(|(keys, action)| {
+        Row::new(vec![
+            Cell::from(format!(" {keys}")).style(theme.value),
+            Cell::from(action),
+        ])
+    });
+    let area = f.area();
+    let (block, area) = popup(
+        f,
+        " Keys ",
+        width.min(area.width),
+        height.min(area.height),
+        theme,
+    );
+    let table = Table::new(rows, [Constraint::Length(keys_width), Constraint::Fill(1)])
+        .block(block.padding(Padding::vertical(1)));
+    f.render_widget(table, area);
+}
+
+/// The periods of the hourly forecast, if there are any.
+fn hourly_periods(state: &State) -> Option<&[forecast::Results]> {
+    match state.hourly_forecast.value {
+        Some(ref forecast) if !forecast.properties.periods.is_empty() => {
+            Some(&forecast.properties.periods)
+        }
+        _ => None,
+    }
+}
+
+/// Charts of the hourly forecast: temperature above, wind speed below.
+fn display_hourly(f: &mut Frame, area: Rect, state: &State) {
+    let units = state.config.units;
+    let staleness = state
+        .hourly_forecast
+        .staleness("fetched", None, state.clock.now());
+    let block = panel(" Hourly ", staleness, false, &state.theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(periods) = hourly_periods(state) else {
+        f.render_widget(Paragraph::new(format!("\n  {MISSING}")), inner);
+        return;
+    };
+    let [temperature_area, wind_area] =
+        Layout::vertical([Constraint::Fill(3), Constraint::Fill(2)]).areas(inner);
+    let temperatures = hourly_temperatures(units, periods);
+    let title = format!(" Temperature ({})", units.temperature_label());
+    let chart = temperature_chart(state, periods, &temperatures, Some(title));
+    f.render_widget(chart, temperature_area);
+    let title = format!(" Wind ({})", units.speed_label().to_lowercase());
+    f.render_widget(wind_chart(state, periods, title), wind_area);
+}
+
+/// The hourly temperature chart on its own.
+fn temperature_panel(f: &mut Frame, area: Rect, state: &State) {
+    let staleness = state
+        .hourly_forecast
+        .staleness("fetched", None, state.clock.now());
+    let title = match state.config.units {
+        Units::Imperial => " Temperature (F) ",
+        Units::Metric => " Temperature (C) ",
+    };
+    let block = panel(title, staleness, false, &state.theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(periods) = hourly_periods(state) else {
+        f.render_widget(Paragraph::new(format!("\n  {MISSING}")), inner);
+        return;
+    };
+    let temperatures = hourly_temperatures(state.config.units, periods);
+    let chart = temperature_chart(state, periods, &temperatures, None);
+    f.render_widget(chart, inner);
+}
+
+/// Each period's temperature, against its index.
+fn hourly_temperatures(units: Units, periods: &[forecast::Results]) -> Vec<(f64, f64)> {
+    periods
+        .iter()
+        .enumerate()
+        .filter_map(|(i, period)| {
+            let temp = units.fahrenheit(period.temperature?);
+            Some((i as f64, temp as f64))
+        })
+        .collect()
+}
+
+fn temperature_chart<'a>(
+    state: &State,
+    periods: &[forecast::Results],
+    temperatures: &'a [(f64, f64)],
+    title: Option<String>,
+) -> Chart<'a> {
+    let theme = &state.theme;
+    let hour = |period: Option<&forecast::Results>| {
+        period
+            .and_then(|period| state.clock.parse(period.start_time.as_deref()?))
+            .map_or(MISSING.to_string(), |time| state.clock.time(&time))
+    };
+    let (low, high) = temperatures
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(low, high), &(_, temp)| {
+            (low.min(temp), high.max(temp))
+        });
+    let (low, high) = if low <= high {
+        (low.floor() - 1.0, high.ceil() + 1.0)
+    } else {
+        (0.0, 1.0)
+    };
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(theme.value)
+        .data(temperatures);
+    let mut chart = Chart::new(vec![dataset])
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, (periods.len() - 1) as f64])
+                .labels([
+                    hour(periods.first()),
+                    hour(periods.get(periods.len() / 2)),
+                    hour(periods.last()),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([low, high])
+                .labels([format!("{low:.0}"), format!("{high:.0}")]),
+        );
+    if let Some(title) = title {
+        chart = chart.block(Block::default().title(Span::styled(title, theme.title)));
+    }
+    chart
+}
+
+fn wind_chart<'a>(state: &State, periods: &[forecast::Results], title: String) -> BarChart<'a> {
+    let units = state.config.units;
+    let bars: Vec<Bar> = periods
+        .iter()
+        .map(|period| {
+            let speed = units.mph(period.max_wind_mph().unwrap_or(0.0));
+            Bar::default()
+                .value(speed.round() as u64)
+                .text_value(String::new())
+        })
+        .collect();
+    BarChart::default()
+        .block(Block::default().title(Span::styled(title, state.theme.title)))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(1)
+        .bar_style(state.theme.value)
+}
+
+#[test]
+fn test_format_age() {
+    assert_eq!(format_age(chrono::Duration::seconds(-5)), "0s");
+    assert_eq!(format_age(chrono::Duration::seconds(59)), "59s");
+    assert_eq!(format_age(chrono::Duration::minutes(12)), "12m");
+    assert_eq!(format_age(chrono::Duration::minutes(72)), "1h 12m");
+    assert_eq!(format_age(chrono::Duration::hours(50)), "2d 2h");
+}
+
+#[test]
+fn test_format_relative() {
+    assert_eq!(format_relative(chrono::Duration::seconds(-5)), "just now");
+    assert_eq!(format_relative(chrono::Duration::seconds(59)), "just now");
+    assert_eq!(format_relative(chrono::Duration::minutes(12)), "12 min ago");
+    assert_eq!(
+        format_relative(chrono::Duration::minutes(185)),
+        "3 h 5 min ago"
+    );
+    assert_eq!(format_relative(chrono::Duration::hours(50)), "2 days ago");
+}
+
+#[test]
+fn test_freshness() {
+    let text = |line: &Line| {
+        line.spans
+            .iter()
+            .map(|span| &*span.content)
+            .collect::<String>()
+    };
+
+    // The demo observation is 7 minutes old.
+    let mut state = demo_state();
+    let [_, date] = headline_lines(&state);
+    assert_eq!(text(&date), " 18-10-2026 14:53 · 7 min ago");
+    assert_eq!(date.spans[2].style, Style::default());
+    state.config.display.max_age = 5;
+    let [_, date] = headline_lines(&state);
+    assert_eq!(date.spans[2].style, state.theme.warning);
+    state.config.display.max_age = u64::MAX;
+    let [_, date] = headline_lines(&state);
+    assert_eq!(date.spans[2].style, Style::default());
+    state.config.display.max_age = 5;
+
+    let [countdown, fetched] = freshness_lines(&state);
+    assert_eq!(text(&countdown), "Next refresh in 10s ");
+    assert_eq!(
+        text(&fetched),
+        "Fetched obs 15:00 alerts 15:00 forecast 15:00 hourly 15:00 "
+    );
+
+    let mut app = App::new("KMSN", state);
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(text(&freshness_lines(&app.state)[0]), "Refreshing… ");
+    app.state.next_refresh = None;
+    assert_eq!(text(&freshness_lines(&app.state)[0]), "");
+
+    // An interval past the end of time never comes round.
+    app.state.config.refresh.interval = 9_000_000_000_000_000;
+    app.state
+        .apply(Update::fetch(&crate::source::StaticSource::demo(), "KMSN"));
+    assert_eq!(app.state.next_refresh, None);
+}
+
+#[test]
+fn test_staleness() {
+    let clock = test_clock();
+    let mut resource = Resource::default();
+    resource.update(Some(Ok(())), clock.now() - chrono::Duration::minutes(5));
+    assert_eq!(resource.staleness("fetched", None, clock.now()), None);
+
+    resource.update(None, clock.now());
+    assert_eq!(
+        resource.staleness("fetched", None, clock.now()).unwrap(),
+        "fetched 5m ago, refresh failed 1x"
+    );
+}
+
+#[test]
+fn test_fill_in() {
+    use crate::source::StaticSource;
+
+    // KMSN is missing its temperature and humidity, KC29's observation is three hours old, and
+    // KMKX's is up to date. The list of nearby stations starts with KMSN itself.
+    let mut source = StaticSource::demo();
+    let observation = source.observation.as_mut().unwrap();
+    observation.properties.temperature.value = None;
+    observation.properties.relative_humidity.value = None;
+    let neighbour = |id: &str, timestamp: &str, temperature: f32| {
+        let mut station = source.station.clone().unwrap();
+        station.properties.station_identifier = id.to_string();
+        let mut observation = source.observation.clone().unwrap();
+        observation.properties.timestamp = timestamp.to_string();
+        observation.properties.temperature.value = Some(temperature);
+        observation.properties.relative_humidity.value = Some(75.0);
+        (station, observation)
+    };
+    source.nearby = vec![
+        neighbour("KMSN", "2026-10-18T14:53:00Z", 0.0),
+        neighbour("KC29", "2026-10-18T11:55:00Z", 5.0),
+        neighbour("KMKX", "2026-10-18T14:55:00Z", 12.5),
+    ];
+    source.nearby[0].1 = source.observation.clone().unwrap();
+
+    let now = test_clock().now();
+    let mut fallback = Fallback::default();
+    let mut update = Update::fetch(&source, "KMSN");
+    update.fill_in(&source, &fallback, now);
+    let properties = &update.observation.as_ref().unwrap().properties;
+    assert_eq!(properties.temperature.value, None);
+
+    fallback.enabled = true;
+    let mut update = Update::fetch(&source, "KMSN");
+    update.fill_in(&source, &fallback, now);
+    let properties = &update.observation.as_ref().unwrap().properties;
+    assert_eq!(properties.temperature.value, Some(12.5));
+    assert_eq!(properties.temperature.source.as_deref(), Some("KMKX"));
+    assert_eq!(properties.relative_humidity.source.as_deref(), Some("KMKX"));
+    assert_eq!(properties.wind_speed.source, None);
+
+    let mut state = test_state();
+    state.apply(update);
+    crate::golden::assert_golden("fallback_80x24", &render(80, 24, &state));
+
+    // Only the nearest station is tried.
+    fallback.stations = 1;
+    let mut update = Update::fetch(&source, "KMSN");
+    update.fill_in(&source, &fallback, now);
+    let properties = &update.observation.as_ref().unwrap().properties;
+    assert_eq!(properties.temperature.value, None);
+
+    // With an age too large to check, KC29's three-hour-old observation will do.
+    fallback.max_age = u64::MAX;
+    fallback.stations = 2;
+    let mut update = Update::fetch(&source, "KMSN");
+    update.fill_in(&source, &fallback, now);
+    let properties = &update.observation.as_ref().unwrap().properties;
+    assert_eq!(properties.temperature.source.as_deref(), Some("KC29"));
+}
+
+#[test]
+fn test_fetch_without_station() {
+    use crate::source::StaticSource;
+
+    let source = StaticSource {
+        station: None,
+        ..StaticSource::demo()
+    };
+    let update = Update::fetch(&source, "KMSN");
+    assert!(update.observation.is_ok());
+    assert!(update.station.is_err());
+    assert!(update.forecast.is_none());
+
+    let mut state = test_state();
+    state.apply(update);
+    assert!(state.has_data());
+    assert_eq!(state.alerts.failures, 1);
+}
+
+#[cfg(test)]
+fn render(width: u16, height: u16, state: &State) -> Buffer {
+    render_view(width, height, state, &mut View::default())
+}
+
+#[cfg(test)]
+fn render_view(width: u16, height: u16, state: &State, view: &mut View) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| draw(f, "KMSN", state, view)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The demo observation was made at 14:53 UTC.
+#[cfg(test)]
+fn test_clock() -> Clock {
+    let now = DateTime::parse_from_rfc3339("2026-10-18T15:00:00Z").unwrap();
+    Clock::fixed(now.to_utc()).with_zone(crate::clock::Zone::Utc)
+}
+
+#[cfg(test)]
+fn test_state() -> State {
+    State {
+        clock: test_clock(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+fn demo_state() -> State {
+    let mut state = test_state();
+    state.apply(Update::fetch(&crate::source::StaticSource::demo(), "KMSN"));
+    state
+}
+
+#[test]
+fn test_ui_snapshots() {
+    let state = demo_state();
+    for (width, height) in [(60, 30), (80, 24), (120, 40), (160, 50)] {
+        let name = format!("dashboard_{width}x{height}");
+        crate::golden::assert_golden(&name, &render(width, height, &state));
+    }
+}
+
+#[test]
+fn test_theme_snapshots() {
+    for (name, theme) in [
+        ("light", Theme::light()),
+        ("monochrome", Theme::monochrome()),
+    ] {
+        let state = State {
+            theme,
+            ..demo_state()
+        };
+        crate::golden::assert_golden(&format!("{name}_80x24"), &render(80, 24, &state));
+    }
+}
+
+#[test]
+fn test_offline_snapshot() {
+    let snapshot = Snapshot {
+        observation: Some(Saved {
+            fetched_at: test_clock().now(),
+            value: crate::source::StaticSource::demo().observation.unwrap(),
+        }),
+        ..Default::default()
+    };
+    let mut state = test_state();
+    state.restore(snapshot);
+    crate::golden::assert_golden("offline_100x30", &render(100, 30, &state));
+}
+
+#[test]
+fn test_too_small_snapshot() {
+    crate::golden::assert_golden("too_small_30x10", &render(30, 10, &demo_state()));
+}
+
+#[test]
+fn test_breakpoints() {
+    let of = |width, height| Breakpoint::of(Rect::new(0, 0, width, height));
+    assert_eq!(of(39, 40), None);
+    assert_eq!(of(100, 11), None);
+    assert_eq!(of(60, 30), Some(Breakpoint::Narrow));
+    assert_eq!(of(80, 24), Some(Breakpoint::Medium));
+    assert_eq!(of(149, 40), Some(Breakpoint::Medium));
+    assert_eq!(of(150, 40), Some(Breakpoint::Wide));
+}
+
+#[test]
+fn test_error_snapshot() {
+    let mut state = test_state();
+    let now = state.clock.now();
+    state.station.update(
+        Some(Err(noaa::Error::Problem {
+            url: "https://api.weather.gov/stations/KMSN".to_string(),
+            status: reqwest::StatusCode::NOT_FOUND,
+            problem: noaa::Problem {
+                title: Some("Not Found".to_string()),
+                detail: Some("Station KMSN not found".to_string()),
+                correlation_id: Some("b3a1c5e0".to_string()),
+            },
+        })),
+        now,
+    );
+    crate::golden::assert_golden("error_100x20", &render(100, 20, &state));
+}
+
+#[test]
+fn test_worker_panic() {
+    let (tx, rx) = mpsc::channel();
+    spawn_worker(tx, || panic!("lost the network"))
+        .join()
+        .unwrap();
+    match rx.recv() {
+        Ok(Message::Crashed(panic)) => assert_eq!(panic, "lost the network"),
+        _ => panic!("expected a crash"),
+    }
+}
+
+#[test]
+fn test_app_messages() {
+    let unreachable = || noaa::Error::CircuitOpen {
+        retry_in: Duration::from_secs(30),
+    };
+    let snapshot = Snapshot {
+        observation: Some(Saved {
+            fetched_at: test_clock().now(),
+            value: crate::source::StaticSource::demo().observation.unwrap(),
+        }),
+        ..Default::default()
+    };
+    let mut app = App::new(
+        "KMSN",
+        State {
+            fallback: Some(snapshot),
+            ..test_state()
+        },
+    );
+
+    app.update(Message::Tick);
+    assert_eq!(app.ticks, 1);
+
+    let update = Update {
+        id: "KMSN".to_string(),
+        observation: Err(unreachable()),
+        station: Err(unreachable()),
+        alerts: None,
+        forecast: None,
+        hourly_forecast: None,
+        stats: noaa::Stats::default(),
+    };
+    assert!(!update.arrived());
+    app.update(Message::FetchFailed(update));
+    assert!(app.state.offline.is_some());
+    assert!(app.state.has_data());
+
+    app.update(Message::DataArrived(Update::fetch(
+        &crate::source::StaticSource::demo(),
+        "KMSN",
+    )));
+    assert!(app.state.offline.is_none());
+    assert!(app.state.station.value.is_some());
+
+    app.update(Message::Key(KeyEvent::new(
+        KeyCode::Char('q'),
+        KeyModifiers::NONE,
+    )));
+    assert!(app.quit);
+}
+
+#[test]
+fn test_quit() {
+    let app = || App::new("KMSN", test_state());
+
+    let mut ctrl_c = app();
+    ctrl_c.update(Message::Key(KeyEvent::new(
+        KeyCode::Char('c'),
+        KeyModifiers::CONTROL,
+    )));
+    assert!(ctrl_c.quit);
+
+    let mut plain_c = app();
+    plain_c.update(Message::Key(KeyEvent::new(
+        KeyCode::Char('c'),
+        KeyModifiers::NONE,
+    )));
+    assert!(!plain_c.quit);
+
+    let mut signal = app();
+    signal.update(Message::Quit);
+    assert!(signal.quit);
+}
+
+#[cfg(test)]
+fn demo_app() -> App {
+    App::new("KMSN", demo_state())
+}
+
+#[cfg(test)]
+fn press(app: &mut App, code: KeyCode) {
+    app.update(Message::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+}
+
+#[test]
+fn test_navigation() {
+    let mut app = demo_app();
+    assert_eq!(app.view.focus, Panel::Current);
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.view.details);
+
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.view.focus, Panel::Alerts);
+    assert_eq!(app.view.alerts.selected(), Some(0));
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.view.alerts.selected(), Some(0));
+
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.view.focus, Panel::Forecast);
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(app.view.forecast.selected(), Some(3));
+    for _ in 0..10 {
+        press(&mut app, KeyCode::Down);
+    }
+    assert_eq!(app.view.forecast.selected(), Some(5));
+    press(&mut app, KeyCode::Char('k'));
+    assert_eq!(app.view.forecast.selected(), Some(4));
+
+    press(&mut app, KeyCode::Enter);
+    assert!(app.view.details);
+    // Keys other than the ones that close the popup are ignored while it is open.
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.view.focus, Panel::Forecast);
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.view.details);
+
+    press(&mut app, KeyCode::Char('?'));
+    assert!(app.view.help);
+    press(&mut app, KeyCode::Char('?'));
+    assert!(!app.view.help);
+
+    press(&mut app, KeyCode::Char('r'));
+    assert!(app.refresh);
+
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::BackTab);
+    assert_eq!(app.view.focus, Panel::Forecast);
+}
+
+#[test]
+fn test_key_actions() {
+    let mut app = demo_app();
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.state.config.units, Units::Metric);
+    press(&mut app, KeyCode::Char('2'));
+    press(&mut app, KeyCode::Right);
+    assert_eq!(app.view.screen, Screen::Dashboard);
+
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::End);
+    assert_eq!(app.view.forecast.selected(), Some(5));
+    press(&mut app, KeyCode::Home);
+    assert_eq!(app.view.forecast.selected(), Some(0));
+
+    // The vim preset, with a binding of the user's own that takes `j` from scrolling.
+    let mut keys: crate::config::Keys =
+        toml::from_str("preset = \"vim\"\nrefresh = \"j\"").unwrap();
+    app.state.keymap = Keymap::new(&keys).unwrap();
+    press(&mut app, KeyCode::Char('G'));
+    assert_eq!(app.view.forecast.selected(), Some(5));
+    press(&mut app, KeyCode::Char('j'));
+    assert!(app.refresh);
+    assert_eq!(app.view.forecast.selected(), Some(5));
+
+    keys.bindings.insert(Action::Quit, "x".to_string());
+    app.state.keymap = Keymap::new(&keys).unwrap();
+    press(&mut app, KeyCode::Char('q'));
+    assert!(!app.quit);
+    press(&mut app, KeyCode::Char('x'));
+    assert!(app.quit);
+
+    // Ctrl-C quits even when quit is bound to other keys, or Ctrl-C to something else.
+    keys.bindings.insert(Action::Refresh, "ctrl-c".to_string());
+    app.state.keymap = Keymap::new(&keys).unwrap();
+    app.quit = false;
+    app.update(Message::Key(KeyEvent::new(
+        KeyCode::Char('c'),
+        KeyModifiers::CONTROL,
+    )));
+    assert!(app.quit);
+}
+
+#[test]
+fn test_switch_station() {
+    let mut app = demo_app();
+    app.stations = vec!["KMSN".to_string(), "KRHI".to_string()];
+    app.update(Message::DataArrived(Update::fetch(
+        &crate::source::StaticSource::demo(),
+        "KMSN",
+    )));
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::Char('2'));
+
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.station, "KRHI");
+    assert!(app.switched);
+    assert!(!app.state.has_data());
+    assert_eq!(app.view.screen, Screen::Hourly);
+    assert_eq!(app.view.focus, Panel::Current);
+
+    // A refresh of the old station that finishes late is dropped.
+    app.update(Message::DataArrived(Update::fetch(
+        &crate::source::StaticSource::demo(),
+        "KMSN",
+    )));
+    assert!(!app.state.has_data());
+
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.station, "KMSN");
+}
+
+#[test]
+fn test_inline_snapshots() {
+    for (width, height) in [(120, 10), (60, 8)] {
+        let mut app = demo_app();
+        app.inline = true;
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        crate::golden::assert_golden(
+            &format!("inline_{width}x{height}"),
+            terminal.backend().buffer(),
+        );
+    }
+}
+
+#[test]
+fn test_popup_snapshots() {
+    let mut app = demo_app();
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Enter);
+    crate::golden::assert_golden(
+        "details_100x30",
+        &render_view(100, 30, &app.state, &mut app.view),
+    );
+
+    let mut app = demo_app();
+    press(&mut app, KeyCode::Char('?'));
+    crate::golden::assert_golden(
+        "help_100x30",
+        &render_view(100, 30, &app.state, &mut app.view),
+    );
+    let mut app = demo_app();
+    press(&mut app, KeyCode::Char('i'));
+    assert!(app.view.info);
+    crate::golden::assert_golden(
+        "station_100x30",
+        &render_view(100, 30, &app.state, &mut app.view),
+    );
+    press(&mut app, KeyCode::Char('q'));
+    assert!(app.quit);
+    press(&mut app, KeyCode::Char('i'));
+    assert!(!app.view.info);
+}
+
+#[cfg(test)]
+fn mouse(app: &mut App, kind: MouseEventKind, target: Target) {
+    let (area, _) = app
+        .view
+        .targets
+        .iter()
+        .find(|&&(_, t)| t == target)
+        .copied()
+        .unwrap();
+    app.update(Message::Mouse(MouseEvent {
+        kind,
+        column: area.x + area.width / 2,
+        row: area.y + area.height / 2,
+        modifiers: KeyModifiers::NONE,
+    }));
+}
+
+#[test]
+fn test_mouse() {
+    let click = MouseEventKind::Down(MouseButton::Left);
+    let mut app = demo_app();
+    render_view(100, 30, &app.state, &mut app.view);
+
+    mouse(&mut app, click, Target::Item(Panel::Forecast, 1));
+    assert_eq!(app.view.focus, Panel::Forecast);
+    assert_eq!(app.view.forecast.selected(), Some(1));
+
+    mouse(
+        &mut app,
+        MouseEventKind::ScrollDown,
+        Target::Item(Panel::Forecast, 1),
+    );
+    assert_eq!(app.view.forecast.selected(), Some(2));
+
+    // The wheel scrolls the list under the pointer, not the focused one.
+    mouse(
+        &mut app,
+        MouseEventKind::ScrollDown,
+        Target::Panel(Panel::Alerts),
+    );
+    assert_eq!(app.view.focus, Panel::Forecast);
+    assert_eq!(app.view.alerts.selected(), Some(0));
+
+    mouse(&mut app, click, Target::Panel(Panel::Current));
+    assert_eq!(app.view.focus, Panel::Current);
+
+    mouse(&mut app, click, Target::Screen(Screen::Hourly));
+    assert_eq!(app.view.screen, Screen::Hourly);
+    crate::golden::assert_golden(
+        "hourly_100x30",
+        &render_view(100, 30, &app.state, &mut app.view),
+    );
+    mouse(&mut app, click, Target::Screen(Screen::Dashboard));
+    assert_eq!(app.view.screen, Screen::Dashboard);
+}
+
+#[test]
+fn test_alert_hook() {
+    let out = std::env::temp_dir().join(format!("wx-hook-{}", process::id()));
+    _ = std::fs::remove_file(&out);
+    let mut state = test_state();
+    state.config.hooks.on_alert = Some(format!(
+        "echo \"$WX_STATION $WX_SEVERITY $WX_EVENT\" >> {}",
+        out.display()
+    ));
+    let mut app = App::new("KMSN", state);
+
+    // The hook runs once per alert, however often it is fetched.
+    for _ in 0..2 {
+        app.update(Message::DataArrived(Update::fetch(
+            &crate::source::StaticSource::demo(),
+            "KMSN",
+        )));
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut text = String::new();
+    while text.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+        text = std::fs::read_to_string(&out).unwrap_or_default();
+    }
+    thread::sleep(Duration::from_millis(100));
+    text = std::fs::read_to_string(&out).unwrap_or_default();
+    _ = std::fs::remove_file(&out);
+    assert_eq!(text, "KMSN Moderate Frost Advisory\n");
+}
+
+#[test]
+fn test_pressure_tendency() {
+    let start: DateTime<Utc> = "2026-10-18T10:00:00Z".parse().unwrap();
+    let hours = |h: i64| start + chrono::Duration::hours(h);
+    assert_eq!(pressure_tendency(&[]), None);
+    assert_eq!(pressure_tendency(&[(hours(0), 101000.0)]), None);
+
+    // Only the last three hours count.
+    let readings = [
+        (hours(0), 100000.0),
+        (hours(1), 101000.0),
+        (hours(2), 101100.0),
+        (hours(4), 100900.0),
+    ];
+    assert_eq!(
+        pressure_tendency(&readings),
+        Some((-100.0, chrono::Duration::hours(3)))
+    );
+    assert_eq!(
+        pressure_tendency(&readings[..3]),
+        Some((1100.0, chrono::Duration::hours(2)))
+    );
+}
 
-    f.render_widget(list, chunks[1]);
+#[test]
+fn test_layout_snapshot() {
+    let mut state = demo_state();
+    state.config.layout = Some(
+        toml::from_str(
+            r#"
+            columns = [
+                { rows = [{ widget = "wind" }, { widget = "pressure", size = 9 }] },
+                { rows = [{ widget = "astronomy", size = 8 }, { widget = "temperature" }] },
+            ]
+            "#,
+        )
+        .unwrap(),
+    );
+    // Readings from earlier in the morning, so the pressure has a trend.
+    let (latest, pressure) = state.pressure[0];
+    state.pressure = (0..7)
+        .rev()
+        .map(|i| {
+            let time = latest - chrono::Duration::minutes(30 * i);
+            (time, pressure - 25.0 * i as f32)
+        })
+        .collect();
+    crate::golden::assert_golden("layout_100x30", &render(100, 30, &state));
 }
@@ -0,0 +1,153 @@
+//! Sunrise, sunset and the phase of the moon, accurate to a minute or two, which is plenty for a
+//! weather dashboard.
+
+use std::f64::consts::TAU;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+/// Julian day of 2000-01-01 12:00 UTC.
+const J2000: f64 = 2451545.0;
+
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH: f64 = 2440587.5;
+
+/// Days from one new moon to the next.
+const SYNODIC_MONTH: f64 = 29.530588853;
+
+/// Julian day of a known new moon, 2000-01-06 18:14 UTC.
+const NEW_MOON: f64 = 2451550.26;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sun {
+    RiseSet(DateTime<Utc>, DateTime<Utc>),
+
+    /// The sun doesn't set, as in a polar summer.
+    AlwaysUp,
+
+    /// The sun doesn't rise, as in a polar winter.
+    AlwaysDown,
+}
+
+impl Sun {
+    /// Sunrise and sunset on `date` at a latitude and longitude in degrees, east positive. Uses the
+    /// sunrise equation, with the usual allowance for refraction and the size of the sun's disc.
+    pub fn on(date: NaiveDate, lat: f64, lon: f64) -> Self {
+        let days = (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()).num_days() as f64;
+        let noon = days - lon / 360.0;
+        let anomaly = (357.5291 + 0.98560028 * noon)
+            .rem_euclid(360.0)
+            .to_radians();
+        let center =
+            1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+        let longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        let transit = J2000 + noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * longitude).sin();
+        let declination = (longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+        let lat = lat.to_radians();
+        let hour_angle = ((-0.833_f64).to_radians().sin() - lat.sin() * declination.sin())
+            / (lat.cos() * declination.cos());
+        match hour_angle {
+            ..-1.0 => Sun::AlwaysUp,
+            1.0.. => Sun::AlwaysDown,
+            _ => {
+                let half_day = hour_angle.acos().to_degrees() / 360.0;
+                Sun::RiseSet(
+                    from_julian(transit - half_day),
+                    from_julian(transit + half_day),
+                )
+            }
+        }
+    }
+
+    /// How long the sun is up.
+    pub fn daylight(&self) -> TimeDelta {
+        match *self {
+            Sun::RiseSet(rise, set) => set - rise,
+            Sun::AlwaysUp => TimeDelta::days(1),
+            Sun::AlwaysDown => TimeDelta::zero(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moon {
+    /// How far through its cycle the moon is, from 0 at new moon through 0.5 at full moon.
+    pub phase: f64,
+}
+
+impl Moon {
+    pub fn at(time: DateTime<Utc>) -> Self {
+        let phase = ((to_julian(time) - NEW_MOON) / SYNODIC_MONTH).rem_euclid(1.0);
+        Moon { phase }
+    }
+
+    /// The fraction of the disc that is lit.
+    pub fn illumination(&self) -> f64 {
+        (1.0 - (self.phase * TAU).cos()) / 2.0
+    }
+
+    pub fn name(&self) -> &'static str {
+        const NAMES: [&str; 8] = [
+            "New Moon",
+            "Waxing Crescent",
+            "First Quarter",
+            "Waxing Gibbous",
+            "Full Moon",
+            "Waning Gibbous",
+            "Last Quarter",
+            "Waning Crescent",
+        ];
+        NAMES[(self.phase * 8.0 + 0.5) as usize % 8]
+    }
+}
+
+fn to_julian(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + UNIX_EPOCH
+}
+
+fn from_julian(day: f64) -> DateTime<Utc> {
+    let secs = ((day - UNIX_EPOCH) * 86400.0).round() as i64;
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
+
+#[test]
+fn test_sun() {
+    let near = |time: DateTime<Utc>, expected: &str| {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        assert!(
+            (time - expected).num_minutes().abs() <= 2,
+            "{time} vs {expected}"
+        );
+    };
+
+    // Madison, where the sun rises at about 7:13 and sets at about 18:11 CDT on this day.
+    let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    let Sun::RiseSet(rise, set) = Sun::on(date, 43.14, -89.35) else {
+        panic!("no sunrise");
+    };
+    near(rise, "2026-10-18T12:13:00Z");
+    near(set, "2026-10-18T23:11:00Z");
+
+    // Utqiaġvik, Alaska, in midsummer and midwinter.
+    let summer = NaiveDate::from_ymd_opt(2026, 6, 21).unwrap();
+    let winter = NaiveDate::from_ymd_opt(2026, 12, 21).unwrap();
+    assert_eq!(Sun::on(summer, 71.29, -156.79), Sun::AlwaysUp);
+    assert_eq!(Sun::on(winter, 71.29, -156.79), Sun::AlwaysDown);
+    assert_eq!(Sun::AlwaysDown.daylight(), TimeDelta::zero());
+}
+
+#[test]
+fn test_moon() {
+    let at = |time: &str| Moon::at(time.parse().unwrap());
+
+    // The full moon of 26 October 2026 and the new moon of 9 November.
+    let full = at("2026-10-26T04:12:00Z");
+    assert_eq!(full.name(), "Full Moon");
+    assert!(full.illumination() > 0.99, "{full:?}");
+    let new = at("2026-11-09T07:02:00Z");
+    assert_eq!(new.name(), "New Moon");
+    assert!(new.illumination() < 0.01, "{new:?}");
+    assert_eq!(at("2026-10-18T14:53:00Z").name(), "First Quarter");
+}
@@ -1,5 +1,13 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
 use clap::builder::{styling::AnsiColor, Styles};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::clock::Zone;
+use crate::export::Format;
+use crate::units::Units;
 
 const ABOUT: &str = "NOAA weather TUI";
 
@@ -11,6 +19,59 @@ your station identifier by checking your local weather on https://noaa.gov.
 
 The weather station is saved, so subsequent runs of `wx` will use the last station unless otherwise
 specified.
+
+Press ? in the dashboard to see the key bindings.
+
+`--inline 10` draws a smaller dashboard in the ten lines below the prompt, refreshing in place, and
+leaves the last frame in the scrollback on quitting.
+
+Every successful fetch is also saved, so `wx --offline` can show the last known data without a
+network connection. `wx` falls back to the saved data on its own if the network is down at start-up.
+
+Settings are read from config.toml in $XDG_CONFIG_HOME/wx (see `wx config path`). Besides the
+options below it can set a default station, saved locations, alert filters, key bindings and a
+command to run for each new alert. `[profiles.NAME]` tables override the rest of the file when
+running `wx --profile NAME`, and flags override both. `wx config show` prints the result.
+
+`theme` in the `[display]` section picks the colours: default, light, high-contrast, monochrome, or
+the name of a file in the themes directory beside config.toml. NO_COLOR turns colours off.
+
+Dates and times follow `date_format` and `time_format` in `[display]`, as strftime patterns.
+`twelve_hour = true` switches to a 12-hour clock, and `relative_dates = true` shows alert times as
+e.g. \"Tonight 8 PM\" or \"Tue 6 AM\". `time_zone = \"station\"` shows times in the station's own
+zone.
+
+Small stations often leave values out or stop reporting for hours. With `enabled = true` in the
+`[fallback]` section, missing values, or all of them once the observation is older than `max_age`
+minutes, are filled in from the nearest stations with fresh observations. Each one is followed by
+the identifier of the station it came from.
+
+`[layout]` arranges the dashboard as rows and columns of widgets: current, alerts, forecast, hourly,
+temperature, wind, pressure and astronomy. For example:
+
+    [layout]
+    columns = [
+        { size = \"40%\", rows = [{ widget = \"current\" }, { widget = \"pressure\", size = 9 }] },
+        { widget = \"forecast\" },
+    ]
+
+`[keys]` rebinds keys: `preset = \"vim\"` adds h, l, g, G, Ctrl-D and Ctrl-U, and lines such as
+`refresh = \"r f5\"` give an action its own keys. The help overlay shows the keys in effect. `s`
+cycles through the saved locations.
+
+`wx snapshot` draws the dashboard once, without a terminal, and prints it as text with colours
+(ansi), plain text (txt), an HTML page or an SVG image. `--width` and `--height` set its size and
+`--output` writes it to a file. It takes a station like the dashboard, and the dashboard's flags
+go before it, e.g. `wx --units metric snapshot KMSN`.
+
+The API endpoint and User-Agent can be set in the `[api]` section of the config file, or with the
+WX_API_BASE, WX_USER_AGENT and WX_CONTACT environment variables. The NWS asks clients to include a
+contact address, so please set `contact` (or WX_CONTACT) to your email.
+
+`--record DIR` saves every response from NOAA to DIR, and `--replay DIR` plays them back without a
+network connection. Attaching a recording to a bug report makes the problem easy to reproduce.
+`--now` pins the clock (e.g. to the time of the recording) and `--time-scale` speeds it up, which
+helps when looking at how the dashboard ages.
 ";
 
 const STYLES: Styles = Styles::styled()
@@ -22,6 +83,136 @@ const STYLES: Styles = Styles::styled()
 #[derive(Parser, Debug)]
 #[command(version, styles=STYLES, about=ABOUT, long_about = LONG_ABOUT)]
 pub struct Args {
-    #[arg(help = "NOAA weather station identifier (e.g. KMSN, KELP, etc.)")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(help = "NOAA weather station identifier (e.g. KMSN, KELP, etc.) or saved location")]
     pub station: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use the named profile from the config file"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(long, value_enum, help = "Units to show values in")]
+    pub units: Option<Units>,
+
+    #[arg(long, value_name = "SECS", help = "Seconds between refreshes")]
+    pub refresh: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "ZONE",
+        help = "Zone to show times in: local, utc, station, an offset or a name such as America/Chicago"
+    )]
+    pub time_zone: Option<Zone>,
+
+    #[arg(long, help = "Show the last saved data without contacting NOAA")]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["offline", "record", "replay"],
+        help = "Show built-in sample data instead of contacting NOAA"
+    )]
+    pub demo: bool,
+
+    #[arg(
+        long,
+        value_name = "LINES",
+        value_parser = clap::value_parser!(u16).range(4..),
+        help = "Draw a compact dashboard LINES tall below the prompt instead of filling the screen"
+    )]
+    pub inline: Option<u16>,
+
+    #[arg(long, value_name = "DIR", help = "Save every NOAA response to DIR")]
+    pub record: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "record",
+        help = "Serve NOAA responses from DIR instead of the network"
+    )]
+    pub replay: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "TIME",
+        help = "Pretend the current time is TIME (RFC 3339, e.g. 2026-10-18T15:00:00Z)"
+    )]
+    pub now: Option<DateTime<Utc>>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "now",
+        value_parser = parse_time_scale,
+        help = "Run the clock N times faster than real time"
+    )]
+    pub time_scale: Option<f64>,
+}
+
+/// How much faster than real time the clock may run: a year a second.
+const MAX_TIME_SCALE: f64 = 31_536_000.0;
+
+fn parse_time_scale(value: &str) -> Result<f64, String> {
+    let scale: f64 = value.parse().map_err(|err| format!("{err}"))?;
+    if scale.is_finite() && scale > 0.0 && scale <= MAX_TIME_SCALE {
+        Ok(scale)
+    } else {
+        Err(format!("must be above 0 and at most {MAX_TIME_SCALE}"))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Draw the dashboard once and print it, e.g. to paste into a chat
+    Snapshot {
+        #[arg(help = "NOAA weather station identifier or saved location, as for the dashboard")]
+        station: Option<String>,
+
+        #[arg(long, default_value_t = 120, help = "Width in columns")]
+        width: u16,
+
+        #[arg(long, default_value_t = 40, help = "Height in rows")]
+        height: u16,
+
+        #[arg(long, value_enum, default_value_t, help = "What to write")]
+        format: Format,
+
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Write to FILE instead of standard output"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the settings in effect, after applying the profile and flags
+    Show,
+
+    /// Print where the config file is read from
+    Path,
+}
+
+#[test]
+fn test_time_scale() {
+    let parse = |scale: &str| Args::try_parse_from(["wx", "--time-scale", scale]);
+    assert_eq!(parse("60").unwrap().time_scale, Some(60.0));
+    for scale in ["0", "-1", "NaN", "inf", "1e300", "fast"] {
+        assert!(parse(scale).is_err(), "{scale}");
+    }
 }
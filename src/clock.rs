@@ -0,0 +1,369 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Where the current time comes from, and which zone and format times are shown in. Everything
+/// that asks "what time is it?" goes through a `Clock`, so tests can pin or speed up time.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    time: Time,
+    zone: Zone,
+    format: Format,
+
+    /// The station's zone, once known, for [`Zone::Station`].
+    station_zone: Option<Tz>,
+}
+
+#[derive(Debug, Clone, Default)]
+enum Time {
+    #[default]
+    System,
+
+    Fixed(DateTime<Utc>),
+
+    /// Starts at `start` when the clock is created and runs `speed` times faster than real time.
+    Simulated {
+        start: DateTime<Utc>,
+        origin: Instant,
+        speed: f64,
+    },
+}
+
+/// `elapsed` sped up `speed` times. Unlike `Duration::mul_f64` it saturates rather than panics,
+/// and a speed that isn't positive stops the clock.
+fn scaled(elapsed: Duration, speed: f64) -> Duration {
+    if speed.is_nan() || speed <= 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::try_from_secs_f64(elapsed.as_secs_f64() * speed).unwrap_or(Duration::MAX)
+}
+
+/// The zone times are displayed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Zone {
+    /// The machine's zone.
+    #[default]
+    Local,
+
+    Utc,
+
+    Offset(FixedOffset),
+
+    /// A zone from the tz database, e.g. `America/Chicago`.
+    Named(Tz),
+
+    /// The weather station's zone, or the machine's until the station's details arrive.
+    Station,
+}
+
+/// How dates and times are written, as `strftime` patterns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    pub date: String,
+    pub time: String,
+
+    /// Whether times are on a 12-hour clock, which shortens relative times to e.g. "8 PM".
+    pub twelve_hour: bool,
+
+    /// Write times within a week of today as e.g. "Tonight 8 PM" or "Tue 6 AM".
+    pub relative: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self {
+            date: "%d-%m-%Y".to_string(),
+            time: "%H:%M".to_string(),
+            twelve_hour: false,
+            relative: false,
+        }
+    }
+}
+
+impl Clock {
+    pub fn fixed(at: DateTime<Utc>) -> Self {
+        Self {
+            time: Time::Fixed(at),
+            ..Default::default()
+        }
+    }
+
+    pub fn simulated(start: DateTime<Utc>, speed: f64) -> Self {
+        Self {
+            time: Time::Simulated {
+                start,
+                origin: Instant::now(),
+                speed,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn with_zone(self, zone: Zone) -> Self {
+        Self { zone, ..self }
+    }
+
+    pub fn with_format(self, format: Format) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Sets the station's zone from its tz database name. Unknown names are ignored.
+    pub fn set_station_zone(&mut self, name: &str) {
+        if let Ok(zone) = name.parse() {
+            self.station_zone = Some(zone);
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match self.time {
+            Time::System => Utc::now(),
+            Time::Fixed(at) => at,
+            Time::Simulated {
+                start,
+                origin,
+                speed,
+            } => {
+                let elapsed = chrono::Duration::from_std(scaled(origin.elapsed(), speed))
+                    .unwrap_or(chrono::Duration::MAX);
+                start
+                    .checked_add_signed(elapsed)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            }
+        }
+    }
+
+    /// The time on this clock when `instant` was.
+    pub fn at(&self, instant: Instant) -> DateTime<Utc> {
+        let elapsed = match self.time {
+            Time::Simulated { speed, .. } => scaled(instant.elapsed(), speed),
+            Time::System | Time::Fixed(_) => instant.elapsed(),
+        };
+        let elapsed = chrono::Duration::from_std(elapsed).unwrap_or(chrono::Duration::MAX);
+        self.now()
+            .checked_sub_signed(elapsed)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Converts `time` to the display zone.
+    pub fn local<Tz: chrono::TimeZone>(&self, time: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        match (self.zone, self.station_zone) {
+            (Zone::Local, _) | (Zone::Station, None) => time.with_timezone(&Local).fixed_offset(),
+            (Zone::Utc, _) => time.with_timezone(&Utc).fixed_offset(),
+            (Zone::Offset(offset), _) => time.with_timezone(&offset),
+            (Zone::Named(zone), _) | (Zone::Station, Some(zone)) => {
+                time.with_timezone(&zone).fixed_offset()
+            }
+        }
+    }
+
+    /// The time of day of `time` in the display zone.
+    pub fn time<Tz: chrono::TimeZone>(&self, time: &DateTime<Tz>) -> String {
+        self.local(time).format(&self.format.time).to_string()
+    }
+
+    /// The date and time of `time` in the display zone.
+    pub fn date_time<Tz: chrono::TimeZone>(&self, time: &DateTime<Tz>) -> String {
+        let time = self.local(time);
+        format!(
+            "{} {}",
+            time.format(&self.format.date),
+            time.format(&self.format.time)
+        )
+    }
+
+    /// As [`Clock::date_time`], or relative to today if the format asks for it, e.g. "Tonight
+    /// 8 PM", "Tomorrow 6 AM" or "Tue 6 AM".
+    pub fn when<Tz: chrono::TimeZone>(&self, time: &DateTime<Tz>) -> String {
+        if !self.format.relative {
+            return self.date_time(time);
+        }
+        let time = self.local(time);
+        let days = (time.date_naive() - self.local(&self.now()).date_naive()).num_days();
+        let hour = match (self.format.twelve_hour, time.minute()) {
+            (true, 0) => time.format("%-I %p").to_string(),
+            (true, _) => time.format("%-I:%M %p").to_string(),
+            (false, _) => time.format(&self.format.time).to_string(),
+        };
+        match days {
+            0 if time.hour() >= 18 => format!("Tonight {hour}"),
+            0 => format!("Today {hour}"),
+            1 => format!("Tomorrow {hour}"),
+            -1 => format!("Yesterday {hour}"),
+            2..7 => format!("{} {hour}", time.weekday()),
+            _ => self.date_time(&time),
+        }
+    }
+
+    /// Parses an RFC 3339 timestamp from the API into the display zone.
+    pub fn parse(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        let time = DateTime::parse_from_rfc3339(timestamp).ok()?;
+        Some(self.local(&time))
+    }
+}
+
+impl From<Zone> for String {
+    fn from(zone: Zone) -> Self {
+        zone.to_string()
+    }
+}
+
+impl TryFrom<String> for Zone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// `local`, `utc`, `station`, a fixed offset such as `-05:00`, or a name such as
+/// `America/Chicago`.
+impl FromStr for Zone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "local" => Ok(Zone::Local),
+            "utc" | "z" => Ok(Zone::Utc),
+            "station" => Ok(Zone::Station),
+            _ => FixedOffset::from_str(value)
+                .map(Zone::Offset)
+                .or_else(|_| value.parse().map(Zone::Named))
+                .map_err(|_| format!("invalid time zone `{value}`")),
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Zone::Local => write!(f, "local"),
+            Zone::Utc => write!(f, "utc"),
+            Zone::Offset(offset) => write!(f, "{offset}"),
+            Zone::Named(zone) => write!(f, "{}", zone.name()),
+            Zone::Station => write!(f, "station"),
+        }
+    }
+}
+
+#[test]
+fn test_fixed_clock() {
+    let at = DateTime::parse_from_rfc3339("2026-10-18T15:00:00Z")
+        .unwrap()
+        .to_utc();
+    let clock = Clock::fixed(at).with_zone("-05:00".parse().unwrap());
+    assert_eq!(clock.now(), at);
+    assert_eq!(
+        clock.local(&clock.now()).to_rfc3339(),
+        "2026-10-18T10:00:00-05:00"
+    );
+    assert_eq!(
+        clock
+            .parse("2026-10-18T18:00:00+00:00")
+            .unwrap()
+            .to_rfc3339(),
+        "2026-10-18T13:00:00-05:00"
+    );
+}
+
+#[test]
+fn test_simulated_clock() {
+    let start = Utc::now();
+    let clock = Clock::simulated(start, 3600.0);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    // 20ms at an hour per second is over a minute.
+    assert!(clock.now() - start >= chrono::Duration::minutes(1));
+
+    for speed in [-1.0, f64::NAN, 1e300, f64::INFINITY] {
+        let clock = Clock::simulated(start, speed);
+        clock.at(Instant::now() - Duration::from_secs(60));
+        assert!(clock.now() >= start);
+    }
+
+    let fixed = Clock::fixed(start);
+    assert!(
+        fixed.at(Instant::now() - std::time::Duration::from_secs(60))
+            <= start - chrono::Duration::minutes(1)
+    );
+}
+
+#[test]
+fn test_parse_zone() {
+    assert_eq!("UTC".parse(), Ok(Zone::Utc));
+    assert_eq!("local".parse(), Ok(Zone::Local));
+    assert_eq!(
+        "+05:30".parse(),
+        Ok(Zone::Offset(FixedOffset::east_opt(19800).unwrap()))
+    );
+    assert_eq!("station".parse(), Ok(Zone::Station));
+    assert_eq!(
+        "America/Chicago".parse(),
+        Ok(Zone::Named(chrono_tz::America::Chicago))
+    );
+    assert_eq!(
+        Zone::Named(chrono_tz::America::Chicago).to_string(),
+        "America/Chicago"
+    );
+    assert!("Mars/Olympus".parse::<Zone>().is_err());
+}
+
+#[test]
+fn test_station_zone() {
+    let at = DateTime::parse_from_rfc3339("2026-10-18T15:00:00Z")
+        .unwrap()
+        .to_utc();
+    let mut clock = Clock::fixed(at).with_zone(Zone::Station);
+    clock.set_station_zone("America/Chicago");
+    assert_eq!(clock.local(&at).to_rfc3339(), "2026-10-18T10:00:00-05:00");
+
+    // In winter the same zone is an hour further behind.
+    let winter = DateTime::parse_from_rfc3339("2026-12-18T15:00:00Z")
+        .unwrap()
+        .to_utc();
+    assert_eq!(
+        clock.local(&winter).to_rfc3339(),
+        "2026-12-18T09:00:00-06:00"
+    );
+}
+
+#[test]
+fn test_formats() {
+    let at = |time: &str| DateTime::parse_from_rfc3339(time).unwrap().to_utc();
+    let clock = Clock::fixed(at("2026-10-18T15:00:00Z")).with_zone(Zone::Utc);
+    assert_eq!(
+        clock.date_time(&at("2026-10-20T06:00:00Z")),
+        "20-10-2026 06:00"
+    );
+
+    let format = Format {
+        date: "%m/%d/%Y".to_string(),
+        time: "%-I:%M %p".to_string(),
+        twelve_hour: true,
+        relative: false,
+    };
+    let clock = clock.with_format(format.clone());
+    assert_eq!(
+        clock.date_time(&at("2026-10-20T06:00:00Z")),
+        "10/20/2026 6:00 AM"
+    );
+    assert_eq!(clock.time(&at("2026-10-18T20:30:00Z")), "8:30 PM");
+
+    let clock = clock.with_format(Format {
+        relative: true,
+        ..format
+    });
+    for (time, expected) in [
+        ("2026-10-18T09:00:00Z", "Today 9 AM"),
+        ("2026-10-18T20:00:00Z", "Tonight 8 PM"),
+        ("2026-10-19T06:30:00Z", "Tomorrow 6:30 AM"),
+        ("2026-10-17T23:00:00Z", "Yesterday 11 PM"),
+        ("2026-10-20T06:00:00Z", "Tue 6 AM"),
+        ("2026-10-28T06:00:00Z", "10/28/2026 6:00 AM"),
+    ] {
+        assert_eq!(clock.when(&at(time)), expected);
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::format::{Item, StrftimeItems};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Args;
+use crate::clock::{Format, Zone};
+use crate::keymap::{Action, Preset};
+use crate::layout::Node;
+use crate::noaa::alerts::{Alerts, Severity};
+use crate::noaa::{default_user_agent, DEFAULT_BASE_URL};
+use crate::units::Units;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Settings read from `config.toml`. Every field is optional.
+///
+/// `[profiles.NAME]` tables hold overrides for `--profile NAME`, laid over the rest of the file
+/// key by key, so `[profiles.cabin.refresh]` only replaces the keys it sets in `[refresh]`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Station to show when none is given on the command line, ahead of the last one used. May be
+    /// the name of a saved location.
+    pub station: Option<String>,
+
+    pub units: Units,
+    pub refresh: Refresh,
+    pub api: Api,
+    pub display: Display,
+    pub fallback: Fallback,
+    pub alerts: AlertFilter,
+
+    /// How the dashboard is arranged. Without one it depends on the size of the terminal.
+    pub layout: Option<Node>,
+
+    pub keys: Keys,
+
+    pub hooks: Hooks,
+
+    /// Names for stations, usable wherever a station identifier is, e.g. `cabin = "KRHI"`.
+    pub locations: BTreeMap<String, String>,
+
+    #[serde(skip_serializing)]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Refresh {
+    /// Seconds between refreshes.
+    pub interval: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Api {
+    /// Base URL of the NWS API, e.g. an internal mirror or a local mock.
+    pub base_url: Option<String>,
+
+    /// Replaces the whole User-Agent header.
+    pub user_agent: Option<String>,
+
+    /// Contact address appended to the default User-Agent, as the NWS asks.
+    pub contact: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Display {
+    /// Zone to show times in: `local`, `utc`, `station`, an offset such as `-05:00`, or a name
+    /// such as `America/Chicago`.
+    pub time_zone: Zone,
+
+    /// `strftime` pattern for dates, e.g. `%m/%d/%Y`.
+    pub date_format: String,
+
+    /// `strftime` pattern for times of day. Without one it follows `twelve_hour`.
+    pub time_format: Option<String>,
+
+    /// Show times as e.g. `8:30 PM` rather than `20:30`.
+    pub twelve_hour: bool,
+
+    /// Show alert times within a week as e.g. `Tonight 8 PM` or `Tue 6 AM`.
+    pub relative_dates: bool,
+
+    /// Colour theme: `default`, `light`, `high-contrast`, `monochrome`, or the name of a file in
+    /// the `themes` directory beside this one.
+    pub theme: Option<String>,
+
+    /// Minutes after which the age of the observation is shown in the warning colour. Stations
+    /// usually report hourly.
+    pub max_age: u64,
+}
+
+/// Filling in the observation from nearby stations, for stations that often report nothing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fallback {
+    /// Fill in missing or stale values from the nearest stations that have them.
+    pub enabled: bool,
+
+    /// Minutes after which an observation is stale, and nearby stations' values are shown
+    /// instead where they are fresher.
+    pub max_age: u64,
+
+    /// How many of the nearest stations to try.
+    pub stations: usize,
+}
+
+/// Which alerts to show. Hidden alerts don't run hooks either.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertFilter {
+    /// Hide alerts less severe than this.
+    pub min_severity: Option<Severity>,
+
+    /// Events to hide, e.g. `["Frost Advisory"]`.
+    pub ignore: Vec<String>,
+}
+
+/// Key bindings: a preset, and keys for actions as space-separated lists, e.g.
+/// `quit = "q ctrl-c"`. Keys given to one action are taken from any other.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Keys {
+    /// `default` or `vim`.
+    #[serde(default)]
+    pub preset: Preset,
+
+    #[serde(flatten)]
+    pub bindings: BTreeMap<Action, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Run with `sh -c` once for each new alert, with `WX_STATION`, `WX_EVENT`, `WX_SEVERITY`
+    /// and `WX_HEADLINE` set.
+    pub on_alert: Option<String>,
+}
+
+impl Default for Refresh {
+    fn default() -> Self {
+        Self { interval: 10 }
+    }
+}
+
+impl Default for Fallback {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age: 90,
+            stations: 3,
+        }
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            time_zone: Zone::default(),
+            date_format: Format::default().date,
+            time_format: None,
+            twelve_hour: false,
+            relative_dates: false,
+            theme: None,
+            max_age: 90,
+        }
+    }
+}
+
+impl Display {
+    /// How to write dates and times. Fails on a pattern chrono can't use.
+    pub fn format(&self) -> Result<Format, String> {
+        let time = match (&self.time_format, self.twelve_hour) {
+            (Some(time), _) => time.clone(),
+            (None, true) => "%-I:%M %p".to_string(),
+            (None, false) => Format::default().time,
+        };
+        for (name, pattern) in [("date_format", &self.date_format), ("time_format", &time)] {
+            if StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+                return Err(format!("[display]: invalid {name} `{pattern}`"));
+            }
+        }
+        Ok(Format {
+            date: self.date_format.clone(),
+            time,
+            twelve_hour: self.twelve_hour,
+            relative: self.relative_dates,
+        })
+    }
+}
+
+impl Refresh {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.max(1))
+    }
+}
+
+impl Fallback {
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age.saturating_mul(60))
+    }
+}
+
+impl Api {
+    /// `WX_API_BASE` takes precedence over the config file.
+    pub fn base_url(&self) -> String {
+        env::var("WX_API_BASE")
+            .ok()
+            .or_else(|| self.base_url.clone())
+            .unwrap_or(DEFAULT_BASE_URL.to_string())
+    }
+
+    /// `WX_USER_AGENT` and `WX_CONTACT` take precedence over the config file.
+    pub fn user_agent(&self) -> String {
+        if let Some(user_agent) = env::var("WX_USER_AGENT").ok().or(self.user_agent.clone()) {
+            return user_agent;
+        }
+        let contact = env::var("WX_CONTACT").ok().or(self.contact.clone());
+        default_user_agent(contact.as_deref())
+    }
+}
+
+impl AlertFilter {
+    pub fn apply(&self, mut alerts: Alerts) -> Alerts {
+        alerts.features.retain(|alert| {
+            let properties = &alert.properties;
+            self.min_severity
+                .is_none_or(|min| properties.severity_level() >= min)
+                && !self
+                    .ignore
+                    .iter()
+                    .any(|event| event.eq_ignore_ascii_case(&properties.event))
+        });
+        alerts
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> Option<PathBuf> {
+    let home = env::var("HOME").ok()?;
+    Some(Path::new(&home).join("Library/Application Support/WX"))
+}
+
+#[cfg(target_os = "linux")]
+fn config_dir() -> Option<PathBuf> {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => Some(Path::new(&dir).join("wx")),
+        _ => Some(Path::new(&env::var("HOME").ok()?).join(".config/wx")),
+    }
+}
+
+impl Config {
+    /// The directory holding the config file and themes.
+    pub fn dir() -> Option<PathBuf> {
+        config_dir()
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Reads the config file and applies `profile`. A missing file is the same as an empty one.
+    pub fn load(profile: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::from_table(toml::Table::new(), profile)?);
+        };
+        let table = match read_to_string(&path) {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|err| format!("{}: {err}", path.display()))?
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => toml::Table::new(),
+            Err(err) => return Err(format!("{}: {err}", path.display()).into()),
+        };
+        Self::from_table(table, profile).map_err(|err| format!("{}: {err}", path.display()).into())
+    }
+
+    fn from_table(mut table: toml::Table, profile: Option<&str>) -> Result<Self, String> {
+        if let Some(name) = profile {
+            let overrides = table
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name))
+                .and_then(toml::Value::as_table)
+                .cloned()
+                .ok_or_else(|| format!("no profile named `{name}`"))?;
+            merge(&mut table, overrides);
+        }
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|err: toml::de::Error| err.to_string())
+    }
+
+    /// Command-line flags take precedence over the file.
+    pub fn apply(&mut self, args: &Args) {
+        if let Some(units) = args.units {
+            self.units = units;
+        }
+        if let Some(interval) = args.refresh {
+            self.refresh.interval = interval;
+        }
+        if let Some(zone) = args.time_zone {
+            self.display.time_zone = zone;
+        }
+    }
+
+    /// The station for a saved location name, or `name` itself.
+    pub fn resolve(&self, name: &str) -> String {
+        self.locations
+            .get(name)
+            .cloned()
+            .unwrap_or(name.to_string())
+    }
+
+    /// The settings in effect, in the config file's format.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+}
+
+/// Lays `overrides` over `table`, merging nested tables rather than replacing them.
+pub fn merge(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge(base, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_profiles() {
+    let table: toml::Table = toml::from_str(
+        r#"
+        station = "KMSN"
+        units = "metric"
+
+        [refresh]
+        interval = 30
+
+        [display]
+        time_zone = "utc"
+
+        [locations]
+        cabin = "KRHI"
+
+        [profiles.cabin]
+        station = "cabin"
+
+        [profiles.cabin.display]
+        theme = "light"
+        "#,
+    )
+    .unwrap();
+
+    let base = Config::from_table(table.clone(), None).unwrap();
+    assert_eq!(base.station.as_deref(), Some("KMSN"));
+    assert_eq!(base.display.theme, None);
+
+    let cabin = Config::from_table(table.clone(), Some("cabin")).unwrap();
+    assert_eq!(cabin.resolve(cabin.station.as_deref().unwrap()), "KRHI");
+    assert_eq!(cabin.units, Units::Metric);
+    assert_eq!(cabin.refresh.interval(), Duration::from_secs(30));
+    // Keys the profile doesn't set are kept.
+    assert_eq!(cabin.display.time_zone, Zone::Utc);
+    assert_eq!(cabin.display.theme.as_deref(), Some("light"));
+
+    let err = Config::from_table(table, Some("beach")).unwrap_err();
+    assert_eq!(err, "no profile named `beach`");
+}
+
+#[test]
+fn test_command_line_overrides() {
+    use clap::Parser;
+
+    let mut config =
+        Config::from_table(toml::from_str("units = \"metric\"").unwrap(), None).unwrap();
+    config.apply(&Args::parse_from([
+        "wx",
+        "--units",
+        "imperial",
+        "--refresh",
+        "60",
+    ]));
+    assert_eq!(config.units, Units::Imperial);
+    assert_eq!(config.refresh.interval, 60);
+
+    let shown = config.to_toml().unwrap();
+    assert!(shown.contains("units = \"imperial\""), "{shown}");
+    assert!(shown.contains("interval = 60"), "{shown}");
+}
+
+#[test]
+fn test_alert_filter() {
+    let alerts: Alerts = crate::source::StaticSource::demo().alerts.unwrap();
+    assert_eq!(alerts.features[0].properties.event, "Frost Advisory");
+
+    let keep = |filter: AlertFilter| filter.apply(alerts.clone()).features.len();
+    assert_eq!(keep(AlertFilter::default()), 1);
+    assert_eq!(
+        keep(AlertFilter {
+            min_severity: Some(Severity::Moderate),
+            ..Default::default()
+        }),
+        1
+    );
+    assert_eq!(
+        keep(AlertFilter {
+            min_severity: Some(Severity::Severe),
+            ..Default::default()
+        }),
+        0
+    );
+    assert_eq!(
+        keep(AlertFilter {
+            ignore: vec!["frost advisory".to_string()],
+            ..Default::default()
+        }),
+        0
+    );
+}
+
+#[test]
+fn test_time_formats() {
+    let display = |text: &str| toml::from_str::<Display>(text).unwrap().format();
+
+    assert_eq!(display("").unwrap(), Format::default());
+    let format = display("twelve_hour = true\nrelative_dates = true").unwrap();
+    assert_eq!(format.time, "%-I:%M %p");
+    assert!(format.relative);
+    let format = display("date_format = \"%m/%d\"\ntime_format = \"%H%M\"").unwrap();
+    assert_eq!((&*format.date, &*format.time), ("%m/%d", "%H%M"));
+    assert_eq!(
+        display("date_format = \"%Q\"").unwrap_err(),
+        "[display]: invalid date_format `%Q`"
+    );
+}
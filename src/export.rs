@@ -0,0 +1,401 @@
+//! Writes a rendered screen out as text, for `wx snapshot`. Each row is cut into runs of cells
+//! with the same style, which become escape sequences, `<span>`s or SVG `<text>` elements.
+
+use std::fmt::Write;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+
+/// A colour as red, green and blue.
+type Rgb = (u8, u8, u8);
+
+/// Colours for the HTML and SVG formats, which have no terminal to take them from.
+const FOREGROUND: Rgb = (0xe5, 0xe5, 0xe5);
+const BACKGROUND: Rgb = (0x1c, 0x1c, 0x1c);
+
+/// The size of a cell in SVG, in pixels.
+// In `u32`, as a wide snapshot is more pixels across than fit in a `u16`.
+const CELL_WIDTH: u32 = 9;
+const CELL_HEIGHT: u32 = 18;
+const FONT_SIZE: u16 = 15;
+
+const FONTS: &str = "ui-monospace, Menlo, Consolas, 'DejaVu Sans Mono', monospace";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// Text with ANSI escape sequences for the colours, for `cat` or `less -R`.
+    #[default]
+    Ansi,
+
+    /// A standalone HTML page.
+    Html,
+
+    Svg,
+
+    /// Plain text without colours.
+    Txt,
+}
+
+pub fn export(buffer: &Buffer, format: Format) -> String {
+    match format {
+        Format::Ansi => ansi(buffer),
+        Format::Html => html(buffer),
+        Format::Svg => svg(buffer),
+        Format::Txt => txt(buffer),
+    }
+}
+
+/// The runs of each row, as the column each starts at, its style and its text.
+fn runs(buffer: &Buffer) -> Vec<Vec<(u16, Style, String)>> {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let mut runs: Vec<(u16, Style, String)> = vec![];
+            for x in area.left()..area.right() {
+                let cell = &buffer[(x, y)];
+                match runs.last_mut() {
+                    Some((_, style, text)) if *style == cell.style() => {
+                        text.push_str(cell.symbol())
+                    }
+                    _ => runs.push((x - area.left(), cell.style(), cell.symbol().to_string())),
+                }
+            }
+            runs
+        })
+        .collect()
+}
+
+fn txt(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in runs(buffer) {
+        let line: String = row.into_iter().map(|(_, _, text)| text).collect();
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+    out
+}
+
+fn ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in runs(buffer) {
+        for (_, style, text) in row {
+            let mut codes = vec!["0".to_string()];
+            for (modifier, code) in [
+                (Modifier::BOLD, "1"),
+                (Modifier::DIM, "2"),
+                (Modifier::ITALIC, "3"),
+                (Modifier::UNDERLINED, "4"),
+                (Modifier::REVERSED, "7"),
+                (Modifier::CROSSED_OUT, "9"),
+            ] {
+                if style.add_modifier.contains(modifier) {
+                    codes.push(code.to_string());
+                }
+            }
+            codes.extend(style.fg.and_then(|color| sgr(color, 30)));
+            codes.extend(style.bg.and_then(|color| sgr(color, 40)));
+            write!(out, "\x1b[{}m{text}", codes.join(";")).unwrap();
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// The SGR parameters that set `color`, with `base` 30 for the foreground or 40 for the
+/// background. `None` for the terminal's own colour.
+fn sgr(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+        Color::Indexed(i) => return Some(format!("{};5;{i}", base + 8)),
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+    };
+    Some(code.to_string())
+}
+
+/// `color` as red, green and blue, using xterm's palette for the named and indexed colours.
+fn rgb(color: Color) -> Option<Rgb> {
+    const NAMED: [Rgb; 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Indexed(i) => i,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    Some(match index {
+        0..16 => NAMED[index as usize],
+        16..232 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// The colours a run is drawn in, after reversing and dimming.
+fn colors(style: Style) -> (Rgb, Option<Rgb>) {
+    let fg = style.fg.and_then(rgb);
+    let bg = style.bg.and_then(rgb);
+    let (fg, bg) = if style.add_modifier.contains(Modifier::REVERSED) {
+        (bg.unwrap_or(BACKGROUND), Some(fg.unwrap_or(FOREGROUND)))
+    } else {
+        (fg.unwrap_or(FOREGROUND), bg)
+    };
+    let fg = if style.add_modifier.contains(Modifier::DIM) {
+        let base = bg.unwrap_or(BACKGROUND);
+        let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+        (mix(fg.0, base.0), mix(fg.1, base.1), mix(fg.2, base.2))
+    } else {
+        fg
+    };
+    (fg, bg)
+}
+
+/// CSS for the bold, italic, underlined and crossed out modifiers.
+fn font_css(style: Style) -> String {
+    let mut css = String::new();
+    if style.add_modifier.contains(Modifier::BOLD) {
+        css.push_str("font-weight:bold;");
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        css.push_str("font-style:italic;");
+    }
+    match (
+        style.add_modifier.contains(Modifier::UNDERLINED),
+        style.add_modifier.contains(Modifier::CROSSED_OUT),
+    ) {
+        (true, true) => css.push_str("text-decoration:underline line-through;"),
+        (true, false) => css.push_str("text-decoration:underline;"),
+        (false, true) => css.push_str("text-decoration:line-through;"),
+        (false, false) => (),
+    }
+    css
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html>").unwrap();
+    writeln!(
+        out,
+        "<head><meta charset=\"utf-8\"><title>wx</title></head>"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<body style=\"margin:0;background:{}\">",
+        hex(BACKGROUND)
+    )
+    .unwrap();
+    write!(
+        out,
+        "<pre style=\"margin:0;padding:1em;color:{};font-family:{FONTS};line-height:1.2\">",
+        hex(FOREGROUND)
+    )
+    .unwrap();
+    for row in runs(buffer) {
+        for (_, style, text) in row {
+            let (fg, bg) = colors(style);
+            let mut css = String::new();
+            if fg != FOREGROUND {
+                write!(css, "color:{};", hex(fg)).unwrap();
+            }
+            if let Some(bg) = bg {
+                write!(css, "background:{};", hex(bg)).unwrap();
+            }
+            css.push_str(&font_css(style));
+            if css.is_empty() {
+                out.push_str(&escape(&text));
+            } else {
+                write!(out, "<span style=\"{css}\">{}</span>", escape(&text)).unwrap();
+            }
+        }
+        out.push('\n');
+    }
+    writeln!(out, "</pre>").unwrap();
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    out
+}
+
+fn svg(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let width = u32::from(area.width) * CELL_WIDTH;
+    let height = u32::from(area.height) * CELL_HEIGHT;
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"{FONTS}\" font-size=\"{FONT_SIZE}\" \
+         xml:space=\"preserve\">"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        hex(BACKGROUND)
+    )
+    .unwrap();
+    for (y, row) in runs(buffer).into_iter().enumerate() {
+        let top = y as u32 * CELL_HEIGHT;
+        for (x, style, text) in row {
+            let (fg, bg) = colors(style);
+            let cells = text.chars().count() as u32;
+            let left = u32::from(x) * CELL_WIDTH;
+            if let Some(bg) = bg {
+                writeln!(
+                    out,
+                    "<rect x=\"{left}\" y=\"{top}\" width=\"{}\" height=\"{CELL_HEIGHT}\" \
+                     fill=\"{}\"/>",
+                    cells * CELL_WIDTH,
+                    hex(bg)
+                )
+                .unwrap();
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+            // Stretching each run to its cells keeps the columns lined up whatever font is used.
+            writeln!(
+                out,
+                "<text x=\"{left}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\" \
+                 fill=\"{}\" style=\"{}\">{}</text>",
+                top + CELL_HEIGHT * 3 / 4,
+                cells * CELL_WIDTH,
+                hex(fg),
+                font_css(style),
+                escape(&text)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+#[cfg(test)]
+fn test_buffer() -> Buffer {
+    use ratatui::layout::Rect;
+
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 2));
+    buffer.set_string(0, 0, "52", Style::new().fg(Color::Cyan));
+    buffer.set_string(3, 0, "F<", Style::new().add_modifier(Modifier::BOLD));
+    buffer.set_string(0, 1, "OFF", Style::new().fg(Color::Black).bg(Color::Yellow));
+    buffer
+}
+
+#[test]
+fn test_txt() {
+    assert_eq!(export(&test_buffer(), Format::Txt), "52 F<\nOFF\n");
+}
+
+#[test]
+fn test_ansi() {
+    assert_eq!(
+        export(&test_buffer(), Format::Ansi),
+        "\x1b[0;36m52\x1b[0m \x1b[0;1mF<\x1b[0m   \x1b[0m\n\
+         \x1b[0;30;43mOFF\x1b[0m     \x1b[0m\n"
+    );
+}
+
+#[test]
+fn test_html() {
+    let html = export(&test_buffer(), Format::Html);
+    assert!(html.contains("<span style=\"color:#00cdcd;\">52</span> "));
+    assert!(html.contains("<span style=\"font-weight:bold;\">F&lt;</span>   \n"));
+    assert!(html.contains("<span style=\"color:#000000;background:#cdcd00;\">OFF</span>"));
+}
+
+#[test]
+fn test_svg() {
+    let svg = export(&test_buffer(), Format::Svg);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"72\" height=\"36\""));
+    assert!(svg.contains(
+        "<text x=\"0\" y=\"13\" textLength=\"18\" lengthAdjust=\"spacingAndGlyphs\" \
+         fill=\"#00cdcd\" style=\"\">52</text>"
+    ));
+    assert!(svg.contains("<rect x=\"0\" y=\"18\" width=\"27\" height=\"18\" fill=\"#cdcd00\"/>"));
+    assert!(svg.contains(">F&lt;</text>"));
+}
+
+#[test]
+fn test_wide_svg() {
+    use ratatui::layout::Rect;
+
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 8000, 20));
+    buffer.set_string(7998, 19, "52", Style::new().bg(Color::Yellow));
+    let svg = export(&buffer, Format::Svg);
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"72000\" height=\"360\"")
+    );
+    assert!(svg.contains("<rect x=\"71982\" y=\"342\" width=\"18\" height=\"18\""));
+}
+
+#[test]
+fn test_palette() {
+    assert_eq!(rgb(Color::Indexed(16)), Some((0, 0, 0)));
+    assert_eq!(rgb(Color::Indexed(196)), Some((0xff, 0, 0)));
+    assert_eq!(rgb(Color::Indexed(244)), Some((0x80, 0x80, 0x80)));
+    assert_eq!(rgb(Color::Reset), None);
+}
@@ -0,0 +1,86 @@
+//! Golden-file tests for rendered screens. Each snapshot holds the text of the buffer followed by
+//! the styled runs of each row, so both layout and colour changes show up in review.
+//!
+//! Run `WX_UPDATE_SNAPSHOTS=1 cargo test` to rewrite the files in `snapshots/` after an intended
+//! change.
+
+use std::env;
+use std::fmt::Write;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::PathBuf;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Style};
+
+const UPDATE_VAR: &str = "WX_UPDATE_SNAPSHOTS";
+
+pub fn assert_golden(name: &str, buffer: &Buffer) {
+    let actual = serialize(buffer);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+
+    if env::var_os(UPDATE_VAR).is_some() {
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}; run with {UPDATE_VAR}=1 to create it",
+            path.display()
+        )
+    });
+    if expected != actual {
+        panic!(
+            "snapshot {name} changed; run with {UPDATE_VAR}=1 to accept\n\
+             --- expected\n{expected}\n+++ actual\n{actual}"
+        );
+    }
+}
+
+fn serialize(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    let mut styles = String::new();
+
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut run: Option<(u16, Style)> = None;
+        for x in area.left()..=area.right() {
+            let cell = (x < area.right()).then(|| &buffer[(x, y)]);
+            if let Some(cell) = cell {
+                line.push_str(cell.symbol());
+            }
+
+            let style = cell.map(|cell| cell.style());
+            if run.is_some_and(|(_, current)| Some(current) != style) {
+                let (start, current) = run.take().unwrap();
+                if !describe(current).is_empty() {
+                    writeln!(styles, "{y:>3} {start:>3}..{x:<3} {}", describe(current)).unwrap();
+                }
+            }
+            if run.is_none() {
+                run = style.map(|style| (x, style));
+            }
+        }
+        writeln!(text, "{}", line.trim_end()).unwrap();
+    }
+
+    format!("{text}--- styles\n{styles}")
+}
+
+fn describe(style: Style) -> String {
+    let mut parts = vec![];
+    if let Some(fg) = style.fg.filter(|&fg| fg != Color::Reset) {
+        parts.push(format!("fg={fg:?}"));
+    }
+    if let Some(bg) = style.bg.filter(|&bg| bg != Color::Reset) {
+        parts.push(format!("bg={bg:?}"));
+    }
+    if !style.add_modifier.is_empty() {
+        parts.push(format!("{:?}", style.add_modifier));
+    }
+    parts.join(" ")
+}
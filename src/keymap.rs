@@ -0,0 +1,412 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Keys;
+
+/// Everything a key can do.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    Refresh,
+    Help,
+    Close,
+    Dashboard,
+    Hourly,
+    NextTab,
+    PreviousTab,
+    NextPanel,
+    PreviousPanel,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    OpenDetail,
+    StationInfo,
+    ToggleUnits,
+    SwitchStation,
+}
+
+impl Action {
+    /// Every action, in the order the help lists them.
+    const ALL: [Action; 20] = [
+        Action::Dashboard,
+        Action::Hourly,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::NextPanel,
+        Action::PreviousPanel,
+        Action::Down,
+        Action::Up,
+        Action::PageDown,
+        Action::PageUp,
+        Action::Top,
+        Action::Bottom,
+        Action::OpenDetail,
+        Action::StationInfo,
+        Action::Close,
+        Action::Refresh,
+        Action::ToggleUnits,
+        Action::SwitchStation,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Refresh => "Refresh now",
+            Action::Help => "Show this help",
+            Action::Close => "Close the popup",
+            Action::Dashboard => "Show the dashboard",
+            Action::Hourly => "Show the hourly charts",
+            Action::NextTab => "Show the next screen",
+            Action::PreviousTab => "Show the previous screen",
+            Action::NextPanel => "Focus the next panel",
+            Action::PreviousPanel => "Focus the previous panel",
+            Action::Down => "Scroll the focused list down",
+            Action::Up => "Scroll the focused list up",
+            Action::PageDown => "Scroll down a page",
+            Action::PageUp => "Scroll up a page",
+            Action::Top => "Select the first item",
+            Action::Bottom => "Select the last item",
+            Action::OpenDetail => "Show details of the selected item",
+            Action::StationInfo => "Show details of the station",
+            Action::ToggleUnits => "Switch between imperial and metric units",
+            Action::SwitchStation => "Show the next saved location",
+        }
+    }
+}
+
+/// Sets of key bindings to start from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Default,
+
+    /// Adds `h` and `l` for screens, `g` and `G` for the ends of lists, and `Ctrl-D`, `Ctrl-U`,
+    /// `Ctrl-F` and `Ctrl-B` for pages.
+    Vim,
+}
+
+const DEFAULT: &[(Action, &str)] = &[
+    (Action::Quit, "q ctrl-c"),
+    (Action::Refresh, "r"),
+    (Action::Help, "?"),
+    (Action::Close, "esc"),
+    (Action::Dashboard, "1"),
+    (Action::Hourly, "2"),
+    (Action::NextTab, "right"),
+    (Action::PreviousTab, "left"),
+    (Action::NextPanel, "tab"),
+    (Action::PreviousPanel, "shift-tab"),
+    (Action::Down, "down j"),
+    (Action::Up, "up k"),
+    (Action::PageDown, "pgdn"),
+    (Action::PageUp, "pgup"),
+    (Action::Top, "home"),
+    (Action::Bottom, "end"),
+    (Action::OpenDetail, "enter"),
+    (Action::StationInfo, "i"),
+    (Action::ToggleUnits, "u"),
+    (Action::SwitchStation, "s"),
+];
+
+/// Laid over [`DEFAULT`] for [`Preset::Vim`].
+const VIM: &[(Action, &str)] = &[
+    (Action::NextTab, "l right"),
+    (Action::PreviousTab, "h left"),
+    (Action::PageDown, "ctrl-d ctrl-f pgdn"),
+    (Action::PageUp, "ctrl-u ctrl-b pgup"),
+    (Action::Top, "g home"),
+    (Action::Bottom, "G end"),
+];
+
+/// A key press as written in the config file, e.g. `q`, `ctrl-c`, `shift-tab` or `pgdn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// The key for an event. Shift is part of the character for character keys, e.g. `G`, and
+    /// only Ctrl and Alt are told apart.
+    fn of(event: &KeyEvent) -> Self {
+        Key {
+            code: event.code,
+            modifiers: event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = s;
+        let mut shift = false;
+        // A key name is never empty, so `ctrl--` is Ctrl and `-`.
+        while let Some((prefix, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+            match prefix.to_lowercase().as_str() {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => shift = true,
+                _ => break,
+            }
+            name = rest;
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_lowercase().as_str() {
+                "tab" if shift => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" | "pageup" => KeyCode::PageUp,
+                "pgdn" | "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{s}`")),
+                },
+            },
+        };
+        if shift && code != KeyCode::BackTab {
+            return Err(format!(
+                "`{s}`: write shifted letters as capitals, e.g. `G`, and Shift only with Tab"
+            ));
+        }
+        Ok(Key { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{c}"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// Which keys do what: a preset, with the config file's `[keys]` laid over it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&Keys::default()).expect("the default keymap is valid")
+    }
+}
+
+impl Keymap {
+    pub fn new(keys: &Keys) -> Result<Self, String> {
+        let mut keymap = Keymap {
+            bindings: Action::ALL.iter().map(|&action| (action, vec![])).collect(),
+        };
+        keymap.bind_all(DEFAULT.iter().copied())?;
+        if keys.preset == Preset::Vim {
+            keymap.bind_all(VIM.iter().copied())?;
+        }
+        keymap
+            .bind_all(
+                keys.bindings
+                    .iter()
+                    .map(|(&action, keys)| (action, keys.as_str())),
+            )
+            .map_err(|err| format!("[keys]: {err}"))?;
+        Ok(keymap)
+    }
+
+    /// Gives each action the keys in its space-separated list, taking them from any other
+    /// action they were bound to. An empty list unbinds the action.
+    fn bind_all<'a>(
+        &mut self,
+        bindings: impl IntoIterator<Item = (Action, &'a str)>,
+    ) -> Result<(), String> {
+        for (action, keys) in bindings {
+            let keys = keys
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<Key>, _>>()?;
+            for (other, bound) in &mut self.bindings {
+                if *other == action {
+                    bound.clone_from(&keys);
+                } else {
+                    bound.retain(|key| !keys.contains(key));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        let key = Key::of(event);
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|&(action, _)| action)
+    }
+
+    /// The keys and description of each action that has keys, for the help overlay.
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        self.bindings
+            .iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let keys: Vec<_> = keys.iter().map(Key::to_string).collect();
+                (keys.join(", "), action.description())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+}
+
+#[test]
+fn test_keys() {
+    for (text, code, modifiers, shown) in [
+        ("q", KeyCode::Char('q'), KeyModifiers::NONE, "q"),
+        ("G", KeyCode::Char('G'), KeyModifiers::NONE, "G"),
+        (
+            "ctrl-c",
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+            "Ctrl-C",
+        ),
+        (
+            "Ctrl-D",
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+            "Ctrl-D",
+        ),
+        ("alt--", KeyCode::Char('-'), KeyModifiers::ALT, "Alt--"),
+        (
+            "shift-tab",
+            KeyCode::BackTab,
+            KeyModifiers::NONE,
+            "Shift-Tab",
+        ),
+        ("pgdn", KeyCode::PageDown, KeyModifiers::NONE, "PgDn"),
+        ("space", KeyCode::Char(' '), KeyModifiers::NONE, "Space"),
+        ("f5", KeyCode::F(5), KeyModifiers::NONE, "F5"),
+        ("esc", KeyCode::Esc, KeyModifiers::NONE, "Esc"),
+    ] {
+        let key: Key = text.parse().unwrap();
+        assert_eq!(key, Key { code, modifiers }, "{text}");
+        assert_eq!(key.to_string(), shown);
+    }
+    assert_eq!(
+        "hyper-x".parse::<Key>().unwrap_err(),
+        "unknown key `hyper-x`"
+    );
+    assert!("shift-g".parse::<Key>().is_err());
+    assert!("f13".parse::<Key>().is_err());
+}
+
+#[test]
+fn test_keymap() {
+    let default = Keymap::default();
+    // Shift comes with capitals and Shift-Tab, and doesn't need spelling out.
+    let shift = KeyModifiers::SHIFT;
+    assert_eq!(
+        default.action(&press(KeyCode::BackTab, shift)),
+        Some(Action::PreviousPanel)
+    );
+    assert_eq!(default.action(&press(KeyCode::Char('G'), shift)), None);
+    assert_eq!(
+        default.action(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        Some(Action::Quit)
+    );
+    assert_eq!(
+        default.action(&press(KeyCode::Char('c'), KeyModifiers::NONE)),
+        None
+    );
+
+    let vim = Keymap::new(&Keys {
+        preset: Preset::Vim,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        vim.action(&press(KeyCode::Char('G'), shift)),
+        Some(Action::Bottom)
+    );
+    assert_eq!(
+        vim.action(&press(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+        Some(Action::PageDown)
+    );
+
+    // Overrides take their keys from other actions.
+    let custom = Keymap::new(&Keys {
+        preset: Preset::Vim,
+        bindings: [
+            (Action::Quit, "x".to_string()),
+            (Action::Refresh, "q f5".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    })
+    .unwrap();
+    let key = |c| custom.action(&press(KeyCode::Char(c), KeyModifiers::NONE));
+    assert_eq!(key('x'), Some(Action::Quit));
+    assert_eq!(key('q'), Some(Action::Refresh));
+    assert_eq!(key('r'), None);
+    assert_eq!(key('g'), Some(Action::Top));
+    let help = custom.help();
+    assert!(help.contains(&("x".to_string(), "Quit")), "{help:?}");
+    assert!(
+        help.contains(&("q, F5".to_string(), "Refresh now")),
+        "{help:?}"
+    );
+
+    let err = Keymap::new(&Keys {
+        bindings: [(Action::Quit, "ctrl-".to_string())].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, "[keys]: unknown key `ctrl-`");
+}
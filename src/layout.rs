@@ -0,0 +1,332 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+/// The things the dashboard can be built from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Widget {
+    /// The latest observation.
+    Current,
+
+    Alerts,
+    Forecast,
+
+    /// Temperature and wind charts of the hourly forecast.
+    Hourly,
+
+    /// The hourly temperature chart alone.
+    Temperature,
+
+    /// Wind speed, gusts and direction, above the hourly wind speeds.
+    Wind,
+
+    /// Barometric pressure and how it has changed.
+    Pressure,
+
+    /// Sunrise, sunset and the phase of the moon.
+    Astronomy,
+}
+
+/// A widget, or rows or columns of further nodes, and how much space it takes from its parent.
+///
+/// In the config file each node is a table with one of `widget`, `rows` or `columns`, and an
+/// optional `size`:
+///
+/// ```toml
+/// [layout]
+/// columns = [
+///     { size = "40%", rows = [{ widget = "current" }, { widget = "pressure", size = 6 }] },
+///     { widget = "forecast" },
+/// ]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "Table", into = "Table")]
+pub struct Node {
+    pub size: Size,
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Widget(Widget),
+    Rows(Vec<Node>),
+    Columns(Vec<Node>),
+}
+
+/// How much of its parent a node takes: a number of cells (`8`), a percentage (`"50%"`), at
+/// least a number of cells (`"min:8"`), or a share of what is left over (`"fill"`, `"fill:2"`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    Length(u16),
+    Percentage(u16),
+    Min(u16),
+    Fill(u16),
+}
+
+impl Default for Size {
+    fn default() -> Self {
+        Size::Fill(1)
+    }
+}
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |n: &str| {
+            n.trim().parse().map_err(|_| {
+                format!("invalid size `{s}`; expected e.g. 8, \"50%\", \"min:8\" or \"fill\"")
+            })
+        };
+        match s.trim() {
+            "fill" => Ok(Size::Fill(1)),
+            s if s.starts_with("fill:") => Ok(Size::Fill(number(&s[5..])?)),
+            s if s.starts_with("min:") => Ok(Size::Min(number(&s[4..])?)),
+            s if s.ends_with('%') => match number(&s[..s.len() - 1])? {
+                percent @ 0..=100 => Ok(Size::Percentage(percent)),
+                _ => Err(format!("`{s}` is more than 100%")),
+            },
+            s => Ok(Size::Length(number(s)?)),
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Size::Length(n) => write!(f, "{n}"),
+            Size::Percentage(n) => write!(f, "{n}%"),
+            Size::Min(n) => write!(f, "min:{n}"),
+            Size::Fill(1) => write!(f, "fill"),
+            Size::Fill(n) => write!(f, "fill:{n}"),
+        }
+    }
+}
+
+impl From<Size> for Constraint {
+    fn from(size: Size) -> Self {
+        match size {
+            Size::Length(n) => Constraint::Length(n),
+            Size::Percentage(n) => Constraint::Percentage(n),
+            Size::Min(n) => Constraint::Min(n),
+            Size::Fill(n) => Constraint::Fill(n),
+        }
+    }
+}
+
+/// Sizes in the config file are numbers of cells or strings.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SizeValue {
+    Length(u16),
+    Text(String),
+}
+
+impl TryFrom<SizeValue> for Size {
+    type Error = String;
+
+    fn try_from(value: SizeValue) -> Result<Self, Self::Error> {
+        match value {
+            SizeValue::Length(n) => Ok(Size::Length(n)),
+            SizeValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<Size> for SizeValue {
+    fn from(size: Size) -> Self {
+        match size {
+            Size::Length(n) => SizeValue::Length(n),
+            size => SizeValue::Text(size.to_string()),
+        }
+    }
+}
+
+/// A node as written in the config file.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Table {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<SizeValue>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    widget: Option<Widget>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rows: Option<Vec<Node>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    columns: Option<Vec<Node>>,
+}
+
+impl TryFrom<Table> for Node {
+    type Error = String;
+
+    fn try_from(table: Table) -> Result<Self, Self::Error> {
+        let size = table
+            .size
+            .map(Size::try_from)
+            .transpose()?
+            .unwrap_or_default();
+        let content = match (table.widget, table.rows, table.columns) {
+            (Some(widget), None, None) => Content::Widget(widget),
+            (None, Some(rows), None) if !rows.is_empty() => Content::Rows(rows),
+            (None, None, Some(columns)) if !columns.is_empty() => Content::Columns(columns),
+            (None, Some(_), None) | (None, None, Some(_)) => {
+                return Err("`rows` and `columns` need at least one node".to_string())
+            }
+            _ => return Err("a layout node needs one of `widget`, `rows` or `columns`".to_string()),
+        };
+        Ok(Node { size, content })
+    }
+}
+
+impl From<Node> for Table {
+    fn from(node: Node) -> Self {
+        let mut table = Table {
+            size: (node.size != Size::default()).then(|| node.size.into()),
+            widget: None,
+            rows: None,
+            columns: None,
+        };
+        match node.content {
+            Content::Widget(widget) => table.widget = Some(widget),
+            Content::Rows(rows) => table.rows = Some(rows),
+            Content::Columns(columns) => table.columns = Some(columns),
+        }
+        table
+    }
+}
+
+impl Node {
+    pub fn widget(widget: Widget, size: Size) -> Self {
+        Node {
+            size,
+            content: Content::Widget(widget),
+        }
+    }
+
+    pub fn rows(size: Size, rows: Vec<Node>) -> Self {
+        Node {
+            size,
+            content: Content::Rows(rows),
+        }
+    }
+
+    pub fn columns(size: Size, columns: Vec<Node>) -> Self {
+        Node {
+            size,
+            content: Content::Columns(columns),
+        }
+    }
+
+    /// Where each widget goes when the layout fills `area`, in the order they appear.
+    pub fn split(&self, area: Rect) -> Vec<(Widget, Rect)> {
+        let mut widgets = vec![];
+        self.split_into(area, &mut widgets);
+        widgets
+    }
+
+    fn split_into(&self, area: Rect, widgets: &mut Vec<(Widget, Rect)>) {
+        let (layout, children) = match self.content {
+            Content::Widget(widget) => {
+                widgets.push((widget, area));
+                return;
+            }
+            Content::Rows(ref rows) => (Layout::vertical(constraints(rows)), rows),
+            Content::Columns(ref columns) => (Layout::horizontal(constraints(columns)), columns),
+        };
+        for (child, area) in children.iter().zip(layout.split(area).iter()) {
+            child.split_into(*area, widgets);
+        }
+    }
+}
+
+fn constraints(nodes: &[Node]) -> Vec<Constraint> {
+    nodes.iter().map(|node| node.size.into()).collect()
+}
+
+#[test]
+fn test_sizes() {
+    for (text, size) in [
+        ("8", Size::Length(8)),
+        ("50%", Size::Percentage(50)),
+        ("min:8", Size::Min(8)),
+        ("fill", Size::Fill(1)),
+        ("fill:2", Size::Fill(2)),
+    ] {
+        assert_eq!(text.parse::<Size>().unwrap(), size);
+        assert_eq!(size.to_string(), text);
+    }
+    assert_eq!(
+        "150%".parse::<Size>().unwrap_err(),
+        "`150%` is more than 100%"
+    );
+    assert!("half".parse::<Size>().is_err());
+}
+
+#[test]
+fn test_layout_config() {
+    #[derive(Deserialize, Serialize)]
+    struct Config {
+        layout: Node,
+    }
+
+    let config: Config = toml::from_str(
+        r#"
+        [layout]
+        columns = [
+            { size = "40%", rows = [{ widget = "current" }, { widget = "pressure", size = 6 }] },
+            { widget = "forecast" },
+        ]
+        "#,
+    )
+    .unwrap();
+    let layout = Node::columns(
+        Size::Fill(1),
+        vec![
+            Node::rows(
+                Size::Percentage(40),
+                vec![
+                    Node::widget(Widget::Current, Size::Fill(1)),
+                    Node::widget(Widget::Pressure, Size::Length(6)),
+                ],
+            ),
+            Node::widget(Widget::Forecast, Size::Fill(1)),
+        ],
+    );
+    assert_eq!(config.layout, layout);
+
+    // Written back out, it reads the same.
+    let text = toml::to_string(&config).unwrap();
+    assert_eq!(toml::from_str::<Config>(&text).unwrap().layout, layout);
+
+    assert_eq!(
+        layout.split(Rect::new(0, 0, 100, 20)),
+        [
+            (Widget::Current, Rect::new(0, 0, 40, 14)),
+            (Widget::Pressure, Rect::new(0, 14, 40, 6)),
+            (Widget::Forecast, Rect::new(40, 0, 60, 20)),
+        ]
+    );
+
+    for (text, expected) in [
+        ("widget = \"radar\"", "unknown variant `radar`"),
+        ("rows = []", "`rows` and `columns` need at least one node"),
+        (
+            "widget = \"current\"\nrows = [{ widget = \"alerts\" }]",
+            "a layout node needs one of `widget`, `rows` or `columns`",
+        ),
+        (
+            "size = 4",
+            "a layout node needs one of `widget`, `rows` or `columns`",
+        ),
+        ("widgets = \"current\"", "unknown field `widgets`"),
+    ] {
+        let err = toml::from_str::<Node>(text).unwrap_err().to_string();
+        assert!(err.contains(expected), "{text}: {err}");
+    }
+}
@@ -1,59 +1,73 @@
 use std::env;
-use std::fs::{create_dir, read_to_string, OpenOptions};
+use std::fs::{self, create_dir_all, read_to_string, OpenOptions};
 use std::path::PathBuf;
 use std::sync::LazyLock;
-use std::{error::Error, io, io::Write};
+use std::{error::Error, io::Write};
 
 use clap::Parser;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{backend::CrosstermBackend, Terminal};
-
-use crate::app::run_app;
-use crate::cli::Args;
-use crate::noaa::{
-    alerts::Alerts, forecast::Forecast, gridpoints::Gridpoints, observation::Observation,
-    station::Station,
-};
+
+use crate::app::{render_once, run_app, Options};
+use crate::cli::{Args, Command, ConfigAction};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::export::export;
+use crate::keymap::Keymap;
+use crate::noaa::Transport;
+use crate::snapshot::Snapshot;
+use crate::source::{StaticSource, WeatherSource};
+use crate::theme::Theme;
 
 mod app;
+mod astronomy;
 mod cli;
+mod clock;
+mod config;
+mod export;
+#[cfg(test)]
+mod golden;
+mod keymap;
+mod layout;
 mod noaa;
+mod snapshot;
+mod source;
+mod terminal;
+mod theme;
 mod units;
 
 const CACHE_FILE: &str = "station";
 
+const SNAPSHOT_DIR: &str = "snapshots";
+
+const DEMO_STATION: &str = "KMSN";
+
 #[cfg(target_os = "macos")]
-static CACHE_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+static STATE_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
     let home = env::var("HOME").ok()?;
     let mut path = PathBuf::new();
     path.push(home);
     path.push("Library/Application Support/WX");
-    path.push(CACHE_FILE);
     Some(path)
 });
 
 #[cfg(target_os = "linux")]
-static CACHE_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
-    let home = env::var("HOME").ok()?;
+static STATE_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
     let mut path = PathBuf::new();
-    path.push(home);
-    path.push(".local/state/wx");
-    path.push(CACHE_FILE);
+    match env::var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => path.push(dir),
+        _ => {
+            path.push(env::var("HOME").ok()?);
+            path.push(".local/state");
+        }
+    }
+    path.push("wx");
     Some(path)
 });
 
-fn get_weather_data(station: &str) -> (Observation, Station, Alerts, Forecast) {
-    let obs = Observation::from_station(station).unwrap_or_default();
-    let stat = Station::from_station(station).unwrap_or_default();
-    let alert = Alerts::from_noaa(stat.zone_id()).unwrap_or_default();
-    let (lat, lon) = stat.coordinates();
-    let grid = Gridpoints::from_coord(lat, lon).unwrap_or_default();
-    let forecast = Forecast::from_noaa(grid.forecast_url()).unwrap_or_default();
-    (obs, stat, alert, forecast)
+static CACHE_PATH: LazyLock<Option<PathBuf>> =
+    LazyLock::new(|| Some(STATE_DIR.as_ref()?.join(CACHE_FILE)));
+
+fn snapshot_dir() -> Option<PathBuf> {
+    Some(STATE_DIR.as_ref()?.join(SNAPSHOT_DIR))
 }
 
 fn get_station_from_cache() -> Option<String> {
@@ -67,9 +81,7 @@ fn get_station_from_cache() -> Option<String> {
 fn cache_station(station: &str) -> Option<()> {
     if let Some(ref path) = *CACHE_PATH {
         let dir = path.parent()?;
-        if !dir.exists() {
-            create_dir(dir).ok()?;
-        }
+        create_dir_all(dir).ok()?;
         let mut file = OpenOptions::new()
             .truncate(true)
             .create(true)
@@ -82,36 +94,109 @@ fn cache_station(station: &str) -> Option<()> {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    if let Some(Command::Snapshot {
+        station: Some(ref station),
+        ..
+    }) = args.command
+    {
+        args.station = Some(station.clone());
+    }
+    let mut config = Config::load(args.profile.as_deref())?;
+    config.apply(&args);
+
+    if let Some(Command::Config { action }) = args.command {
+        match action {
+            ConfigAction::Show => print!("{}", config.to_toml()?),
+            ConfigAction::Path => match Config::path() {
+                Some(path) => println!("{}", path.display()),
+                None => return Err("No config directory; is HOME set?".into()),
+            },
+        }
+        return Ok(());
+    }
 
-    let station = if let Some(station) = args.station {
-        station
+    let station = if let Some(ref station) = args.station {
+        config.resolve(station)
+    } else if args.demo {
+        DEMO_STATION.to_string()
+    } else if let Some(ref station) = config.station {
+        config.resolve(station)
     } else if let Some(station) = get_station_from_cache() {
         station
     } else {
         return Err("Specify weather station identifier.".into());
     };
 
-    // setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // create app and run it
-    let res = run_app(&mut terminal, &station, get_weather_data);
-
-    cache_station(&station);
-
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let snapshot = snapshot_dir()
+        .and_then(|dir| Snapshot::path(&dir, &station))
+        .and_then(|path| Snapshot::load(&path))
+        .filter(|snapshot| !args.demo && !snapshot.is_empty());
+    if args.offline && snapshot.is_none() {
+        return Err(format!("No saved data for {station}.").into());
+    }
+
+    let no_color = theme::no_color(env::var("NO_COLOR").ok().as_deref());
+    let theme = Theme::load(config.display.theme.as_deref(), no_color)?;
+    let keymap = Keymap::new(&config.keys)?;
+
+    let clock = match (args.now, args.time_scale) {
+        (Some(now), _) => Clock::fixed(now),
+        (_, Some(speed)) => Clock::simulated(chrono::Utc::now(), speed),
+        _ => Clock::default(),
+    }
+    .with_zone(config.display.time_zone)
+    .with_format(config.display.format()?);
+
+    let transport = match (args.record, args.replay) {
+        (Some(dir), _) => Transport::Record(dir),
+        (_, Some(dir)) => Transport::Replay(dir),
+        _ => Transport::Live,
+    };
+    let client = noaa::Client::new(&config.api.base_url(), &config.api.user_agent(), transport)?;
+    let source: Box<dyn WeatherSource> = if args.demo {
+        Box::new(StaticSource::demo())
+    } else {
+        snapshot::nws(client, snapshot_dir())
+    };
+
+    let options = Options {
+        offline: args.offline,
+        inline: args.inline.is_some(),
+        clock,
+        config,
+        theme,
+        keymap,
+    };
+
+    if let Some(Command::Snapshot {
+        width,
+        height,
+        format,
+        ref output,
+        ..
+    }) = args.command
+    {
+        let buffer = render_once(&station, source.as_ref(), snapshot, options, width, height)?;
+        let text = export(&buffer, format);
+        match output {
+            Some(path) => fs::write(path, text)?,
+            None => print!("{text}"),
+        }
+        return Ok(());
+    }
+
+    let mut terminal = match args.inline {
+        Some(height) => terminal::Guard::inline(height)?,
+        None => terminal::Guard::enter()?,
+    };
+    let res = run_app(&mut terminal, &station, source, snapshot, options);
+
+    // Quitting, Ctrl-C and SIGTERM all end up here, so the station is remembered either way.
+    if !args.demo {
+        cache_station(res.as_deref().unwrap_or(&station));
+    }
+    drop(terminal);
 
     if let Err(err) = res {
         println!("{:?}", err)
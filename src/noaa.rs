@@ -1,15 +1,153 @@
 use std::default::Default;
+use std::fmt;
+use std::time::Duration;
 
-use chrono::{DateTime, Local};
-use reqwest::blocking::{Client, Response};
-use serde::Deserialize;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
 
-const BASE_URL: &str = "https://api.weather.gov/";
+pub use client::{Client, Stats, Transport};
+
+mod client;
+mod fixture;
+#[cfg(test)]
+mod mock;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.weather.gov/";
+
+const PROJECT_URL: &str = "https://github.com/dabelknap/wx";
+
+/// The NWS asks clients to identify themselves, ideally with a way to get in touch, e.g.
+/// `wx/0.1.0 (+https://github.com/dabelknap/wx; me@example.com)`.
+pub fn default_user_agent(contact: Option<&str>) -> String {
+    let name = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    match contact {
+        Some(contact) => format!("{name} (+{PROJECT_URL}; {contact})"),
+        None => format!("{name} (+{PROJECT_URL})"),
+    }
+}
+
+/// Errors returned while fetching data from the NWS API.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the connection dropped.
+    Network(reqwest::Error),
+
+    /// The request did not complete in time.
+    Timeout(reqwest::Error),
+
+    /// The server answered with a non-success status and no problem details.
+    Status { url: String, status: StatusCode },
+
+    /// The server answered with an `application/problem+json` body.
+    Problem {
+        url: String,
+        status: StatusCode,
+        problem: Problem,
+    },
+
+    /// Replaying a fixture directory that has no response for this URL.
+    NotRecorded { url: String },
+
+    /// Too many requests failed in a row, so the client is not contacting the API until the
+    /// cool-down has passed.
+    CircuitOpen { retry_in: Duration },
+
+    /// The response body did not match the expected shape. `path` is the JSON path of the
+    /// offending value (e.g. `properties.periods[3].temperature`).
+    Decode {
+        url: String,
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Problem details sent by the NWS API alongside error statuses (RFC 7807).
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Problem {
+    pub title: Option<String>,
+
+    pub detail: Option<String>,
+
+    #[serde(rename = "correlationId")]
+    pub correlation_id: Option<String>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network(err) => write!(f, "network error: {err}"),
+            Error::Timeout(_) => write!(f, "request timed out"),
+            Error::Status { url, status } => write!(f, "{status} from {url}"),
+            Error::Problem {
+                status, problem, ..
+            } => {
+                let title = problem.title.as_deref().unwrap_or("unknown problem");
+                write!(f, "{status}: {title}")
+            }
+            Error::NotRecorded { url } => write!(f, "no recorded response for {url}"),
+            Error::CircuitOpen { retry_in } => {
+                write!(
+                    f,
+                    "API unavailable, next attempt in {}s",
+                    retry_in.as_secs()
+                )
+            }
+            Error::Decode { url, path, source } => {
+                write!(f, "unexpected response from {url} at `{path}`: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network(err) | Error::Timeout(err) => Some(err),
+            Error::Decode { source, .. } => Some(source),
+            Error::Status { .. }
+            | Error::Problem { .. }
+            | Error::NotRecorded { .. }
+            | Error::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+impl Error {
+    /// Whether the request may succeed if it is sent again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Network(_) | Error::Timeout(_) => true,
+            Error::Status { status, .. } | Error::Problem { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            Error::NotRecorded { .. } | Error::CircuitOpen { .. } | Error::Decode { .. } => false,
+        }
+    }
+
+    /// Whether the API could not be reached at all, as opposed to answering with an error.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self,
+            Error::Network(_) | Error::Timeout(_) | Error::CircuitOpen { .. }
+        )
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Error::Timeout(err)
+        } else {
+            Error::Network(err)
+        }
+    }
+}
 
 pub mod station {
+    use super::observation::Value;
     use super::*;
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Station {
         pub properties: Properties,
 
@@ -17,9 +155,9 @@ pub mod station {
     }
 
     impl Station {
-        pub fn from_station(station_id: &str) -> Result<Self, reqwest::Error> {
-            let url = format!("{BASE_URL}/stations/{station_id}");
-            get_web_json(&url)?.error_for_status()?.json()
+        pub fn from_station(client: &Client, station_id: &str) -> Result<Self, Error> {
+            let url = client.url(&format!("stations/{station_id}"));
+            client.get_json(&url)
         }
 
         pub fn coordinates(&self) -> (f32, f32) {
@@ -28,18 +166,47 @@ pub mod station {
             (lat, lon)
         }
 
+        pub fn id(&self) -> &str {
+            &self.properties.station_identifier
+        }
+
         pub fn zone_id(&self) -> &str {
             let tokens: Vec<_> = self.properties.forecast.split("/").collect();
             tokens.last().unwrap()
         }
     }
 
-    #[derive(Deserialize, Debug, Default)]
+    /// Observation stations, nearest first.
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    pub struct Stations {
+        pub features: Vec<Station>,
+    }
+
+    impl Stations {
+        pub fn from_noaa(client: &Client, url: &str) -> Result<Self, Error> {
+            client.get_json(url)
+        }
+    }
+
+    /// The last part of a zone URL, e.g. `WIC025` for a county.
+    pub fn zone_name(url: &str) -> &str {
+        url.rsplit('/').next().unwrap_or(url)
+    }
+
+    /// The forecast office and gridpoint covering a station, from the points lookup.
+    #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+    pub struct Grid {
+        pub office: String,
+        pub x: u32,
+        pub y: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Geometry {
         coordinates: (f32, f32),
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Properties {
         pub name: String,
 
@@ -47,6 +214,26 @@ pub mod station {
 
         #[serde(rename = "stationIdentifier")]
         pub station_identifier: String,
+
+        /// The station's zone in the tz database, e.g. `America/Chicago`.
+        #[serde(rename = "timeZone", default)]
+        pub time_zone: Option<String>,
+
+        /// Height above sea level in metres.
+        #[serde(default)]
+        pub elevation: Value<Option<f32>>,
+
+        /// URL of the county zone.
+        #[serde(default)]
+        pub county: Option<String>,
+
+        /// URL of the fire weather zone.
+        #[serde(rename = "fireWeatherZone", default)]
+        pub fire_weather_zone: Option<String>,
+
+        /// Not part of the station's own record; filled in from the points lookup.
+        #[serde(default)]
+        pub grid: Option<Grid>,
     }
 
     impl Default for Properties {
@@ -55,6 +242,11 @@ pub mod station {
                 name: "--".to_string(),
                 forecast: "--".to_string(),
                 station_identifier: "--".to_string(),
+                time_zone: None,
+                elevation: Value::default(),
+                county: None,
+                fire_weather_zone: None,
+                grid: None,
             }
         }
     }
@@ -62,20 +254,21 @@ pub mod station {
 
 pub mod observation {
     use super::*;
+    use crate::clock::Clock;
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Observation {
         pub properties: Properties,
     }
 
     impl Observation {
-        pub fn from_station(station_id: &str) -> Result<Self, reqwest::Error> {
-            let url = format!("{}/stations/{}/observations/latest", BASE_URL, station_id);
-            get_web_json(&url)?.error_for_status()?.json()
+        pub fn from_station(client: &Client, station_id: &str) -> Result<Self, Error> {
+            let url = client.url(&format!("stations/{}/observations/latest", station_id));
+            client.get_json(&url)
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Properties {
         #[serde(rename = "textDescription")]
         pub description: String,
@@ -93,33 +286,97 @@ pub mod observation {
         #[serde(rename = "windSpeed")]
         pub wind_speed: Value<Option<f32>>,
 
+        #[serde(rename = "windGust", default)]
+        pub wind_gust: Value<Option<f32>>,
+
+        /// In pascals.
+        #[serde(rename = "barometricPressure", default)]
+        pub barometric_pressure: Value<Option<f32>>,
+
         #[serde(rename = "relativeHumidity")]
         pub relative_humidity: Value<Option<f32>>,
     }
 
-    impl Default for Properties {
-        fn default() -> Self {
-            let now: DateTime<Local> = Local::now();
+    impl Properties {
+        /// An observation with no values, timestamped with the clock's current time.
+        pub fn empty(clock: &Clock) -> Self {
             Self {
                 description: "--".to_string(),
-                timestamp: now.to_rfc3339(),
+                timestamp: clock.now().to_rfc3339(),
                 temperature: Value::new(None),
                 wind_chill: Value::new(None),
                 wind_direction: Value::new(None),
                 wind_speed: Value::new(None),
+                wind_gust: Value::new(None),
+                barometric_pressure: Value::new(None),
                 relative_humidity: Value::new(None),
             }
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    impl Properties {
+        /// Whether a value is worth looking for at a nearby station: it is missing, or `stale`
+        /// and not already from a nearby station. Wind chill and gusts are left alone, as they
+        /// are often missing for good reason, and so is a wind direction missing in calm air.
+        pub fn wants(&self, stale: bool) -> bool {
+            [
+                &self.temperature,
+                &self.wind_speed,
+                &self.relative_humidity,
+                &self.barometric_pressure,
+            ]
+            .into_iter()
+            .any(|value| value.wanted(stale))
+        }
+
+        /// Fills the values [`Properties::wants`] from `other`, the observation of nearby
+        /// `station`, marking them with its identifier. Wind speed and direction are taken
+        /// together so they describe the same wind.
+        pub fn fill_from(&mut self, other: &Properties, station: &str, stale: bool) {
+            let from = |value: Option<f32>| Value {
+                value,
+                source: Some(station.to_owned()),
+            };
+            for (ours, theirs) in [
+                (&mut self.temperature, &other.temperature),
+                (&mut self.relative_humidity, &other.relative_humidity),
+                (&mut self.barometric_pressure, &other.barometric_pressure),
+            ] {
+                if ours.wanted(stale) && theirs.value.is_some() {
+                    *ours = from(theirs.value);
+                }
+            }
+            if self.wind_speed.wanted(stale)
+                && other.wind_speed.value.is_some()
+                && other.wind_direction.value.is_some()
+            {
+                self.wind_speed = from(other.wind_speed.value);
+                self.wind_direction = from(other.wind_direction.value);
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Value<T> {
         pub value: T,
+
+        /// The nearby station a value was filled in from, if it isn't the station's own.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub source: Option<String>,
     }
 
     impl<T> Value<T> {
         fn new(value: T) -> Self {
-            Self { value }
+            Self {
+                value,
+                source: None,
+            }
+        }
+    }
+
+    impl Value<Option<f32>> {
+        fn wanted(&self, stale: bool) -> bool {
+            self.value.is_none() || (stale && self.source.is_none())
         }
     }
 }
@@ -127,89 +384,349 @@ pub mod observation {
 pub mod gridpoints {
     use super::*;
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Properties {
         forecast: String,
+
+        #[serde(rename = "forecastHourly")]
+        forecast_hourly: String,
+
+        #[serde(rename = "gridId", default)]
+        grid_id: String,
+
+        #[serde(rename = "gridX", default)]
+        grid_x: u32,
+
+        #[serde(rename = "gridY", default)]
+        grid_y: u32,
+
+        /// URL of the observation stations near the point, nearest first.
+        #[serde(rename = "observationStations", default)]
+        observation_stations: String,
     }
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Gridpoints {
         properties: Properties,
     }
 
     impl Gridpoints {
-        pub fn from_coord(lat: f32, lon: f32) -> Result<Self, reqwest::Error> {
-            let url = format!("{BASE_URL}points/{lat},{lon}");
-            get_web_json(&url)?.error_for_status()?.json()
+        pub fn from_coord(client: &Client, lat: f32, lon: f32) -> Result<Self, Error> {
+            let url = client.url(&format!("points/{lat},{lon}"));
+            client.get_json(&url)
         }
 
         pub fn forecast_url(&self) -> &str {
             &self.properties.forecast
         }
+
+        pub fn forecast_hourly_url(&self) -> &str {
+            &self.properties.forecast_hourly
+        }
+
+        pub fn observation_stations_url(&self) -> &str {
+            &self.properties.observation_stations
+        }
+
+        pub fn grid(&self) -> station::Grid {
+            station::Grid {
+                office: self.properties.grid_id.clone(),
+                x: self.properties.grid_x,
+                y: self.properties.grid_y,
+            }
+        }
     }
 }
 
 pub mod forecast {
     use super::*;
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Forecast {
         pub properties: Properties,
     }
 
     impl Forecast {
-        pub fn from_noaa(url: &str) -> Result<Self, reqwest::Error> {
-            get_web_json(url)?.error_for_status()?.json()
+        pub fn from_noaa(client: &Client, url: &str) -> Result<Self, Error> {
+            client.get_json(url)
         }
     }
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Properties {
         pub periods: Vec<Results>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Results {
         pub name: Option<String>,
 
+        #[serde(rename = "startTime")]
+        pub start_time: Option<String>,
+
         pub temperature: Option<f32>,
 
+        /// e.g. "12 mph" or "5 to 10 mph".
+        #[serde(rename = "windSpeed")]
+        pub wind_speed: Option<String>,
+
         #[serde(rename = "shortForecast")]
         pub short_forecast: Option<String>,
+
+        #[serde(rename = "detailedForecast")]
+        pub detailed_forecast: Option<String>,
+    }
+
+    impl Results {
+        /// The highest wind speed in `wind_speed`, in mph.
+        pub fn max_wind_mph(&self) -> Option<f32> {
+            self.wind_speed
+                .as_deref()?
+                .split_whitespace()
+                .filter_map(|word| word.parse::<f32>().ok())
+                .reduce(f32::max)
+        }
     }
 }
 
 pub mod alerts {
     use super::*;
 
-    #[derive(Deserialize, Debug, Default)]
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     pub struct Alerts {
         pub features: Vec<Feature>,
     }
 
     impl Alerts {
-        pub fn from_noaa(zone_id: &str) -> Result<Self, reqwest::Error> {
-            let url = format!("{BASE_URL}/alerts/active/zone/{zone_id}");
-            get_web_json(&url)?.error_for_status()?.json()
+        pub fn from_noaa(client: &Client, zone_id: &str) -> Result<Self, Error> {
+            let url = client.url(&format!("alerts/active/zone/{zone_id}"));
+            client.get_json(&url)
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Feature {
+        /// Unique per alert, and unchanged when the alert is updated.
+        #[serde(default)]
+        pub id: String,
+
         pub properties: Properties,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// CAP severity, least severe first.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Severity {
+        Unknown,
+        Minor,
+        Moderate,
+        Severe,
+        Extreme,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct Properties {
         pub severity: String,
         pub certainty: String,
         pub event: String,
         pub onset: String,
         pub ends: String,
+
+        pub headline: Option<String>,
+
+        pub description: Option<String>,
+
+        /// What to do about it.
+        pub instruction: Option<String>,
+    }
+
+    impl Properties {
+        pub fn severity_level(&self) -> Severity {
+            match self.severity.to_ascii_lowercase().as_str() {
+                "minor" => Severity::Minor,
+                "moderate" => Severity::Moderate,
+                "severe" => Severity::Severe,
+                "extreme" => Severity::Extreme,
+                _ => Severity::Unknown,
+            }
+        }
     }
 }
 
-fn get_web_json(url: &str) -> Result<Response, reqwest::Error> {
-    let client = Client::builder().user_agent("weatherapp").build()?;
-    client.get(url).send()
+#[test]
+fn test_replay() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/KMSN");
+    let client = Client::new(DEFAULT_BASE_URL, "wx-test", Transport::Replay(dir)).unwrap();
+
+    let stat = station::Station::from_station(&client, "KMSN").unwrap();
+    assert_eq!(stat.properties.station_identifier, "KMSN");
+    let obs = observation::Observation::from_station(&client, "KMSN").unwrap();
+    assert_eq!(obs.properties.description, "Mostly Cloudy");
+    let alerts = alerts::Alerts::from_noaa(&client, stat.zone_id()).unwrap();
+    assert_eq!(alerts.features[0].properties.event, "Frost Advisory");
+    let (lat, lon) = stat.coordinates();
+    let grid = gridpoints::Gridpoints::from_coord(&client, lat, lon).unwrap();
+    let forecast = forecast::Forecast::from_noaa(&client, grid.forecast_url()).unwrap();
+    assert_eq!(forecast.properties.periods.len(), 6);
+
+    let missing = observation::Observation::from_station(&client, "KXYZ");
+    assert!(matches!(missing, Err(Error::NotRecorded { .. })));
+}
+
+#[test]
+fn test_mock_station() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let stat = station::Station::from_station(&client, "KMSN").unwrap();
+    assert_eq!(
+        stat.properties.name,
+        "Madison, Dane County Regional Airport"
+    );
+    assert_eq!(stat.zone_id(), "WIZ063");
+    assert_eq!(stat.coordinates(), (43.14, -89.35));
+    assert_eq!(server.hits("stations/KMSN"), 1);
+}
+
+#[test]
+fn test_mock_forecast_chain() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let alerts = alerts::Alerts::from_noaa(&client, "WIZ063").unwrap();
+    assert_eq!(alerts.features.len(), 1);
+    assert_eq!(alerts.features[0].properties.severity, "Moderate");
+
+    let grid = gridpoints::Gridpoints::from_coord(&client, 43.14, -89.35).unwrap();
+    // The forecast URL points at api.weather.gov and must be sent to the mock instead.
+    let forecast = forecast::Forecast::from_noaa(&client, grid.forecast_url()).unwrap();
+    assert_eq!(
+        forecast.properties.periods[1].short_forecast.as_deref(),
+        Some("Patchy Frost")
+    );
+    assert_eq!(server.hits("gridpoints/MKX/37,63/forecast"), 1);
+}
+
+#[test]
+fn test_mock_null_values() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let obs = observation::Observation::from_station(&client, "KNUL").unwrap();
+    assert_eq!(obs.properties.temperature.value, None);
+    assert_eq!(obs.properties.wind_direction.value, Some(230.0));
+}
+
+#[test]
+fn test_mock_nearby_stations() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let grid = gridpoints::Gridpoints::from_coord(&client, 43.14, -89.35).unwrap();
+    let stations = station::Stations::from_noaa(&client, grid.observation_stations_url()).unwrap();
+    let ids: Vec<_> = stations.features.iter().map(station::Station::id).collect();
+    assert_eq!(ids, ["KMSN", "KNUL"]);
+}
+
+#[test]
+fn test_fill_from() {
+    let clock = crate::clock::Clock::default();
+    let mut ours = observation::Properties::empty(&clock);
+    ours.temperature.value = Some(11.1);
+    ours.wind_speed.value = Some(0.0);
+    let mut theirs = observation::Properties::empty(&clock);
+    theirs.temperature.value = Some(12.0);
+    theirs.relative_humidity.value = Some(70.0);
+    theirs.wind_speed.value = Some(18.0);
+    theirs.wind_direction.value = Some(230.0);
+    theirs.wind_chill.value = Some(9.0);
+
+    // Only what is missing, leaving a calm wind and the wind chill as they are.
+    assert!(ours.wants(false));
+    ours.fill_from(&theirs, "KC29", false);
+    assert_eq!(ours.temperature.value, Some(11.1));
+    assert_eq!(ours.temperature.source, None);
+    assert_eq!(ours.relative_humidity.value, Some(70.0));
+    assert_eq!(ours.relative_humidity.source.as_deref(), Some("KC29"));
+    assert_eq!(ours.wind_direction.value, None);
+    assert_eq!(ours.wind_chill.value, None);
+    assert!(ours.wants(false));
+
+    // Everything when stale, but not what is already from a nearby station.
+    theirs.barometric_pressure.value = Some(101660.0);
+    theirs.relative_humidity.value = Some(80.0);
+    ours.fill_from(&theirs, "KMSN", true);
+    assert_eq!(ours.temperature.source.as_deref(), Some("KMSN"));
+    assert_eq!(ours.wind_speed.value, Some(18.0));
+    assert_eq!(ours.wind_direction.source.as_deref(), Some("KMSN"));
+    assert_eq!(ours.relative_humidity.value, Some(70.0));
+    assert!(!ours.wants(true));
+}
+
+#[test]
+fn test_mock_missing_field() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    match station::Station::from_station(&client, "KMIS") {
+        Err(Error::Decode { path, .. }) => assert_eq!(path, "properties"),
+        other => panic!("expected a decode error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_mock_server_error_is_retried() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let stat = station::Station::from_station(&client, "KERR").unwrap();
+    assert_eq!(stat.properties.station_identifier, "KMSN");
+    assert_eq!(server.hits("stations/KERR"), 2);
+    assert_eq!(client.stats().retries, 1);
+}
+
+#[test]
+fn test_mock_problem_details() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    match station::Station::from_station(&client, "KPRB") {
+        Err(Error::Problem {
+            status, problem, ..
+        }) => {
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(problem.title.as_deref(), Some("Not Found"));
+            assert_eq!(problem.correlation_id.as_deref(), Some("b3a1c5e0"));
+        }
+        other => panic!("expected problem details, got {other:?}"),
+    }
+    // Client errors are not retried.
+    assert_eq!(server.hits("stations/KPRB"), 1);
+}
+
+#[test]
+fn test_mock_timeout() {
+    let server = mock::MockServer::nws();
+    let client = Client::for_tests(&server.base_url());
+
+    let result = station::Station::from_station(&client, "KSLO");
+    assert!(matches!(result, Err(Error::Timeout(_))), "{result:?}");
+    assert_eq!(server.hits("stations/KSLO"), 3);
+    assert!(client.stats().last_error.is_some());
+}
+
+#[test]
+fn test_max_wind_mph() {
+    let period = |wind: Option<&str>| forecast::Results {
+        name: None,
+        start_time: None,
+        temperature: None,
+        wind_speed: wind.map(str::to_owned),
+        short_forecast: None,
+        detailed_forecast: None,
+    };
+    assert_eq!(period(Some("12 mph")).max_wind_mph(), Some(12.0));
+    assert_eq!(period(Some("5 to 10 mph")).max_wind_mph(), Some(10.0));
+    assert_eq!(period(Some("calm")).max_wind_mph(), None);
+    assert_eq!(period(None).max_wind_mph(), None);
 }
@@ -0,0 +1,405 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{self, Response};
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+
+use super::fixture::Exchange;
+use super::{Error, Problem, DEFAULT_BASE_URL};
+
+const PROBLEM_JSON: &str = "application/problem+json";
+
+/// How often, and how patiently, a failed GET is sent again.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with "equal jitter": half of the delay is fixed, the other half is
+    /// random, so concurrent clients do not retry in lock-step.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let delay = exp.min(self.max_delay);
+        delay / 2 + delay.mul_f64(jitter() / 2.0)
+    }
+}
+
+/// Request statistics surfaced in the status line.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    /// Retries sent since the client was created.
+    pub retries: u64,
+
+    /// The most recent failure and when it happened. An `Instant`, so it can be placed on
+    /// whichever clock the app runs by.
+    pub last_error: Option<(Instant, String)>,
+
+    /// Set while the circuit breaker is refusing requests.
+    pub open_until: Option<Instant>,
+}
+
+/// Stops the client from contacting the API for a while after repeated failures. The cool-down
+/// doubles each time a trial request fails, up to `max_cooldown`.
+#[derive(Debug)]
+struct CircuitBreaker {
+    threshold: u32,
+    failures: u32,
+    cooldown: Duration,
+    base_cooldown: Duration,
+    max_cooldown: Duration,
+    open_until: Option<Instant>,
+
+    /// Whether the breaker has tripped, so the next request is a trial. A `Retry-After` hold
+    /// alone doesn't count.
+    tripped: bool,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            threshold: 3,
+            failures: 0,
+            cooldown: Duration::from_secs(30),
+            base_cooldown: Duration::from_secs(30),
+            max_cooldown: Duration::from_secs(300),
+            open_until: None,
+            tripped: false,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    fn check(&self, now: Instant) -> Result<(), Error> {
+        match self.open_until {
+            Some(until) if until > now => Err(Error::CircuitOpen {
+                retry_in: until - now,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn success(&mut self) {
+        self.failures = 0;
+        self.cooldown = self.base_cooldown;
+        self.open_until = None;
+        self.tripped = false;
+    }
+
+    /// Records a failed request. `hold` keeps the circuit open for at least that long, which is
+    /// how a long `Retry-After` is honoured.
+    fn failure(&mut self, now: Instant, hold: Option<Duration>) {
+        self.failures += 1;
+        if self.failures >= self.threshold {
+            // A failure while half-open extends the cool-down.
+            if self.tripped {
+                self.cooldown = (self.cooldown * 2).min(self.max_cooldown);
+            }
+            self.tripped = true;
+            self.open_until = Some(now + self.cooldown);
+        }
+        // However long the server asks for, it is tried again after the longest cool-down.
+        if let Some(until) = hold.and_then(|hold| now.checked_add(hold.min(self.max_cooldown))) {
+            self.open_until = Some(self.open_until.map_or(until, |open| open.max(until)));
+        }
+    }
+}
+
+/// Where responses come from.
+#[derive(Debug, Clone, Default)]
+pub enum Transport {
+    /// Straight from the API.
+    #[default]
+    Live,
+
+    /// From the API, saving every response to a fixture directory.
+    Record(PathBuf),
+
+    /// From a fixture directory, without touching the network.
+    Replay(PathBuf),
+}
+
+/// Client for the NWS API. Idempotent GETs are retried with backoff, and a circuit breaker
+/// stops a long outage from being hammered on every refresh.
+#[derive(Debug)]
+pub struct Client {
+    base_url: String,
+    http: blocking::Client,
+    transport: Transport,
+    retry: RetryPolicy,
+    breaker: Mutex<CircuitBreaker>,
+    stats: Mutex<Stats>,
+}
+
+impl Client {
+    /// Creates a client for the API at `base_url` (e.g. a mirror or a local mock) that identifies
+    /// itself as `user_agent`.
+    pub fn new(base_url: &str, user_agent: &str, transport: Transport) -> Result<Self, Error> {
+        let mut base_url = base_url.to_owned();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        let http = blocking::Client::builder().user_agent(user_agent).build()?;
+        Ok(Self {
+            base_url,
+            http,
+            transport,
+            retry: RetryPolicy::default(),
+            breaker: Mutex::default(),
+            stats: Mutex::default(),
+        })
+    }
+
+    /// Whether responses come from the API, rather than a fixture directory.
+    pub fn is_live(&self) -> bool {
+        matches!(self.transport, Transport::Live)
+    }
+
+    /// The URL of an API endpoint, e.g. `stations/KMSN`.
+    pub(super) fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Points URLs handed out by the API (such as a gridpoint's forecast URL) at the configured
+    /// base, so a mirror does not send us back to api.weather.gov.
+    fn resolve(&self, url: &str) -> String {
+        match url.strip_prefix(DEFAULT_BASE_URL) {
+            Some(path) => self.url(path),
+            None => url.to_owned(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let mut stats = self.stats.lock().unwrap().clone();
+        stats.open_until = self.breaker.lock().unwrap().open_until;
+        stats
+    }
+
+    pub(super) fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        self.breaker.lock().unwrap().check(Instant::now())?;
+
+        let url = &self.resolve(url);
+        let mut attempt = 0;
+        loop {
+            let (err, retry_after) = match self.exchange(url).and_then(check_status) {
+                Ok(body) => {
+                    self.breaker.lock().unwrap().success();
+                    return decode(url, &body);
+                }
+                Err(failure) => failure,
+            };
+
+            let delay = retry_after.unwrap_or_else(|| self.retry.backoff(attempt));
+            if !err.is_transient() || attempt >= self.retry.max_retries {
+                return Err(self.record_failure(err, retry_after));
+            }
+            if delay > self.retry.max_delay {
+                // The server asked us to back off for longer than we are willing to block.
+                return Err(self.record_failure(err, Some(delay)));
+            }
+
+            attempt += 1;
+            self.stats.lock().unwrap().retries += 1;
+            // A replayed fixture will not change, so there is nothing to wait for.
+            if !matches!(self.transport, Transport::Replay(_)) {
+                thread::sleep(delay);
+            }
+        }
+    }
+
+    /// Sends a single GET, or looks up its recorded response.
+    fn exchange(&self, url: &str) -> Result<Exchange, (Error, Option<Duration>)> {
+        let fetch = || {
+            get_web_json(&self.http, url)
+                .and_then(|response| Exchange::from_response(url, response))
+                .map_err(|err| (Error::from(err), None))
+        };
+        match self.transport {
+            Transport::Live => fetch(),
+            Transport::Record(ref dir) => {
+                let exchange = fetch()?;
+                // A full disk should not take the app down with it.
+                _ = exchange.save(dir, &self.base_url);
+                Ok(exchange)
+            }
+            Transport::Replay(ref dir) => {
+                Exchange::load(dir, &self.base_url, url).map_err(|err| (err, None))
+            }
+        }
+    }
+
+    fn record_failure(&self, err: Error, hold: Option<Duration>) -> Error {
+        if err.is_transient() {
+            self.breaker.lock().unwrap().failure(Instant::now(), hold);
+        }
+        self.stats.lock().unwrap().last_error = Some((Instant::now(), err.to_string()));
+        err
+    }
+}
+
+#[cfg(test)]
+impl Client {
+    /// A client with a short timeout and quick retries, for tests against the mock server.
+    pub(super) fn for_tests(base_url: &str) -> Self {
+        let mut client = Self::new(base_url, "wx-test", Transport::Live).unwrap();
+        client.http = blocking::Client::builder()
+            .timeout(super::mock::SLOW / 2)
+            .build()
+            .unwrap();
+        client.retry = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        };
+        client
+    }
+}
+
+/// Returns the body of a successful response. Failures carry the server's `Retry-After`, if any.
+fn check_status(exchange: Exchange) -> Result<String, (Error, Option<Duration>)> {
+    let status = StatusCode::from_u16(exchange.status).unwrap_or(StatusCode::BAD_GATEWAY);
+    if status.is_success() {
+        return Ok(exchange.body);
+    }
+
+    let retry_after = exchange
+        .header(RETRY_AFTER.as_str())
+        .and_then(parse_retry_after);
+    let is_problem = exchange
+        .header(CONTENT_TYPE.as_str())
+        .is_some_and(|value| value.starts_with(PROBLEM_JSON));
+    let url = exchange.url;
+    let err = match serde_json::from_str::<Problem>(&exchange.body) {
+        Ok(problem) if is_problem => Error::Problem {
+            url,
+            status,
+            problem,
+        },
+        _ => Error::Status { url, status },
+    };
+    Err((err, retry_after))
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// A number in `[0, 1)`. `RandomState` is randomly keyed, which is all the randomness backoff
+/// jitter needs.
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(nanos.as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub(super) fn decode<T: DeserializeOwned>(url: &str, body: &str) -> Result<T, Error> {
+    let de = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(de).map_err(|err| Error::Decode {
+        url: url.to_owned(),
+        path: err.path().to_string(),
+        source: err.into_inner(),
+    })
+}
+
+fn get_web_json(http: &blocking::Client, url: &str) -> Result<Response, reqwest::Error> {
+    http.get(url).send()
+}
+
+#[test]
+fn test_decode_error_path() {
+    let body = r#"{"properties": {"periods": [{"name": "Tonight", "temperature": "cold"}]}}"#;
+    let err = decode::<super::forecast::Forecast>("forecast", body).unwrap_err();
+    match err {
+        Error::Decode { path, .. } => assert_eq!(path, "properties.periods[0].temperature"),
+        _ => panic!("expected a decode error"),
+    }
+}
+
+#[test]
+fn test_resolve() {
+    let client = Client::new("http://localhost:8080/nws", "wx-test", Transport::Live).unwrap();
+    assert_eq!(
+        client.url("stations/KMSN"),
+        "http://localhost:8080/nws/stations/KMSN"
+    );
+    assert_eq!(
+        client.resolve("https://api.weather.gov/gridpoints/MKX/37,63/forecast"),
+        "http://localhost:8080/nws/gridpoints/MKX/37,63/forecast"
+    );
+    assert_eq!(
+        client.resolve("https://example.com/forecast"),
+        "https://example.com/forecast"
+    );
+}
+
+#[test]
+fn test_circuit_breaker() {
+    let now = Instant::now();
+    let mut breaker = CircuitBreaker::default();
+    breaker.failure(now, None);
+    breaker.failure(now, None);
+    assert!(breaker.check(now).is_ok());
+
+    breaker.failure(now, None);
+    assert!(breaker.check(now).is_err());
+    assert!(breaker.check(now + Duration::from_secs(31)).is_ok());
+
+    // The trial request fails, so the breaker re-opens for twice as long.
+    breaker.failure(now + Duration::from_secs(31), None);
+    assert!(breaker.check(now + Duration::from_secs(61)).is_err());
+    assert!(breaker.check(now + Duration::from_secs(92)).is_ok());
+
+    breaker.success();
+    assert!(breaker.check(now).is_ok());
+
+    // A Retry-After hold before the breaker trips doesn't double the first cool-down.
+    let mut breaker = CircuitBreaker::default();
+    breaker.failure(now, Some(Duration::from_secs(5)));
+    assert!(breaker.check(now + Duration::from_secs(4)).is_err());
+    breaker.failure(now + Duration::from_secs(5), None);
+    breaker.failure(now + Duration::from_secs(5), None);
+    assert!(breaker.check(now + Duration::from_secs(34)).is_err());
+    assert!(breaker.check(now + Duration::from_secs(35)).is_ok());
+
+    // A huge Retry-After holds for the longest cool-down.
+    let mut breaker = CircuitBreaker::default();
+    breaker.failure(now, parse_retry_after("99999999999"));
+    assert!(breaker.check(now + Duration::from_secs(299)).is_err());
+    assert!(breaker.check(now + Duration::from_secs(300)).is_ok());
+    breaker.failure(now, Some(Duration::MAX));
+    assert!(breaker.check(now + Duration::from_secs(300)).is_ok());
+}
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    assert_eq!(parse_retry_after("soon"), None);
+}
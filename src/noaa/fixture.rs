@@ -0,0 +1,122 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use reqwest::blocking::Response;
+use serde::{Deserialize, Serialize};
+
+use super::client::decode;
+use super::Error;
+
+/// A response as received from the API, in a form that can be written to and read back from a
+/// fixture directory.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exchange {
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Exchange {
+    pub fn from_response(url: &str, response: Response) -> Result<Self, reqwest::Error> {
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let body = response.text()?;
+        Ok(Self {
+            url: url.to_owned(),
+            status,
+            headers,
+            body,
+        })
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Reads the response recorded for `url` from a directory recorded against `base_url`. A
+    /// fixture that isn't a recording is a decode error, so it isn't mistaken for a missing one.
+    pub fn load(dir: &Path, base_url: &str, url: &str) -> Result<Self, Error> {
+        let path = fixture_path(dir, base_url, url);
+        let text = read_to_string(&path).map_err(|_| Error::NotRecorded {
+            url: url.to_owned(),
+        })?;
+        decode(&path.display().to_string(), &text)
+    }
+
+    pub fn save(&self, dir: &Path, base_url: &str) -> io::Result<()> {
+        create_dir_all(dir)?;
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        write(fixture_path(dir, base_url, &self.url), text)
+    }
+}
+
+/// Fixtures are named after the part of the URL after `base_url`, so a directory recorded against
+/// one base URL can be replayed against another, e.g. `stations/KMSN` becomes
+/// `stations_KMSN.json`. URLs elsewhere are named after their path and query.
+fn fixture_path(dir: &Path, base_url: &str, url: &str) -> PathBuf {
+    let path = url.strip_prefix(base_url).unwrap_or_else(|| {
+        url.split_once("://")
+            .and_then(|(_, rest)| rest.split_once('/'))
+            .map_or(url, |(_, path)| path)
+    });
+    let name: String = path
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' | ',' => c,
+            _ => '_',
+        })
+        .collect();
+    dir.join(format!("{name}.json"))
+}
+
+#[test]
+fn test_fixture_path() {
+    let dir = Path::new("fixtures");
+    let base = super::DEFAULT_BASE_URL;
+    assert_eq!(
+        fixture_path(
+            dir,
+            base,
+            "https://api.weather.gov/stations/KMSN/observations/latest"
+        ),
+        dir.join("stations_KMSN_observations_latest.json")
+    );
+    assert_eq!(
+        fixture_path(dir, base, "http://127.0.0.1:4000/points/43.14,-89.35"),
+        dir.join("points_43.14,-89.35.json")
+    );
+    assert_eq!(
+        fixture_path(
+            dir,
+            "https://mirror.example.com/nws/",
+            "https://mirror.example.com/nws/stations/KMSN"
+        ),
+        dir.join("stations_KMSN.json")
+    );
+}
+
+#[test]
+fn test_load_fixture() {
+    let dir = std::env::temp_dir().join(format!("wx-fixtures-{}", std::process::id()));
+    create_dir_all(&dir).unwrap();
+    write(dir.join("stations_KBAD.json"), "{ not a recording").unwrap();
+    let base = super::DEFAULT_BASE_URL;
+
+    let corrupt = Exchange::load(&dir, base, "https://api.weather.gov/stations/KBAD");
+    let missing = Exchange::load(&dir, base, "https://api.weather.gov/stations/KXYZ");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(corrupt, Err(Error::Decode { .. })), "{corrupt:?}");
+    assert!(
+        matches!(missing, Err(Error::NotRecorded { .. })),
+        "{missing:?}"
+    );
+}
@@ -0,0 +1,221 @@
+//! A stand-in for api.weather.gov, for tests. It serves the recordings in `fixtures/KMSN` plus
+//! a few misbehaving stations:
+//!
+//! | Station | Behaviour                                                 |
+//! |---------|-----------------------------------------------------------|
+//! | `KMSN`  | Normal responses                                          |
+//! | `KNUL`  | Observation with `null` values                            |
+//! | `KMIS`  | Station without a `name`                                  |
+//! | `KERR`  | 500 on the first request, then normal                     |
+//! | `KPRB`  | 404 with an `application/problem+json` body               |
+//! | `KSLO`  | Responses that take longer than the test client's timeout |
+//!
+//! The stations near the KMSN gridpoint are KMSN itself and then KNUL.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{read_dir, read_to_string};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use super::fixture::Exchange;
+use super::DEFAULT_BASE_URL;
+
+/// How long `KSLO` takes to answer.
+pub const SLOW: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct Reply {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    delay: Duration,
+}
+
+impl Reply {
+    pub fn json(body: &str) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/geo+json".into())],
+            body: body.to_owned(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "text/html".into())],
+            body: format!("<h1>{status}</h1>"),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn problem(status: u16, title: &str, detail: &str) -> Self {
+        let body = serde_json::json!({
+            "type": "https://api.weather.gov/problems/NotFound",
+            "title": title,
+            "status": status,
+            "detail": detail,
+            "instance": "https://api.weather.gov/requests/b3a1c5e0",
+            "correlationId": "b3a1c5e0",
+        });
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "application/problem+json".into())],
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn after(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type Routes = HashMap<String, VecDeque<Reply>>;
+
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<Routes>>,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl MockServer {
+    /// Starts an empty server on a free port. It runs until the test process exits.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Self {
+            addr: listener.local_addr().unwrap(),
+            routes: Arc::default(),
+            hits: Arc::default(),
+        };
+
+        let routes = server.routes.clone();
+        let hits = server.hits.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let routes = routes.clone();
+                let hits = hits.clone();
+                thread::spawn(move || serve(stream, &routes, &hits));
+            }
+        });
+        server
+    }
+
+    /// Starts a server with the canned NWS responses described in the module docs.
+    pub fn nws() -> Self {
+        let server = Self::start();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/KMSN");
+        for entry in read_dir(dir).unwrap().flatten() {
+            let text = read_to_string(entry.path()).unwrap();
+            let exchange: Exchange = serde_json::from_str(&text).unwrap();
+            let path = exchange.url.strip_prefix(DEFAULT_BASE_URL).unwrap();
+            server.route(path, [Reply::json(&exchange.body)]);
+        }
+
+        let station = server.body("stations/KMSN");
+        server.route("stations/KERR", [Reply::status(500), Reply::json(&station)]);
+        server.route(
+            "stations/KMIS",
+            [Reply::json(&station.replace(r#""name""#, r#""label""#))],
+        );
+        server.route(
+            "stations/KPRB",
+            [Reply::problem(404, "Not Found", "Station KPRB not found")],
+        );
+        server.route("stations/KSLO", [Reply::json(&station).after(SLOW)]);
+
+        let mut nearby: serde_json::Value = serde_json::from_str(&station).unwrap();
+        nearby["properties"]["stationIdentifier"] = "KNUL".into();
+        let stations = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [serde_json::from_str::<serde_json::Value>(&station).unwrap(), nearby],
+        });
+        server.route(
+            "gridpoints/MKX/37,63/stations",
+            [Reply::json(&stations.to_string())],
+        );
+
+        let observation = server.body("stations/KMSN/observations/latest");
+        let mut nulls: serde_json::Value = serde_json::from_str(&observation).unwrap();
+        for field in ["temperature", "windChill", "windSpeed", "relativeHumidity"] {
+            nulls["properties"][field]["value"] = serde_json::Value::Null;
+        }
+        server.route(
+            "stations/KNUL/observations/latest",
+            [Reply::json(&nulls.to_string())],
+        );
+        server
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// Answers requests for `path` with `replies` in turn, repeating the last one.
+    pub fn route(&self, path: &str, replies: impl IntoIterator<Item = Reply>) {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let replies = replies.into_iter().collect();
+        self.routes.lock().unwrap().insert(path, replies);
+    }
+
+    /// How many requests were made for `path`.
+    pub fn hits(&self, path: &str) -> usize {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        self.hits.lock().unwrap().get(&path).copied().unwrap_or(0)
+    }
+
+    fn body(&self, path: &str) -> String {
+        let routes = self.routes.lock().unwrap();
+        routes[&format!("/{path}")][0].body.clone()
+    }
+}
+
+fn serve(mut stream: TcpStream, routes: &Mutex<Routes>, hits: &Mutex<HashMap<String, usize>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Skip the headers; the body of a GET is empty.
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+        line.clear();
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let path = target.split('?').next().unwrap_or(target).to_owned();
+    *hits.lock().unwrap().entry(path.clone()).or_default() += 1;
+
+    let reply = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&path) {
+            Some(replies) if replies.len() > 1 => replies.pop_front().unwrap(),
+            Some(replies) => replies[0].clone(),
+            None => Reply::problem(404, "Not Found", &format!("No route for {path}")),
+        }
+    };
+    thread::sleep(reply.delay);
+
+    let reason = StatusCode::from_u16(reply.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("");
+    let mut response = format!("HTTP/1.1 {} {reason}\r\n", reply.status);
+    for (name, value) in &reply.headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        reply.body.len(),
+        reply.body
+    ));
+    _ = stream.write_all(response.as_bytes());
+}
@@ -0,0 +1,239 @@
+use std::fs::{create_dir_all, read_to_string, rename, write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::noaa::{
+    self, alerts::Alerts, forecast::Forecast, observation::Observation, station::Station,
+};
+use crate::source::{Nws, WeatherSource};
+
+/// The last successful fetch of each resource, persisted so `wx` can start without a network.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Snapshot {
+    pub observation: Option<Saved<Observation>>,
+    pub station: Option<Saved<Station>>,
+    pub alerts: Option<Saved<Alerts>>,
+    pub forecast: Option<Saved<Forecast>>,
+    pub hourly_forecast: Option<Saved<Forecast>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Saved<T> {
+    pub fetched_at: DateTime<Utc>,
+    pub value: T,
+}
+
+impl<T: Clone> Saved<T> {
+    /// Saved at the real time, whatever clock the display runs by, so a run with `--now` can't
+    /// make the data look older or newer to later runs.
+    pub fn now(value: &T) -> Self {
+        Self {
+            fetched_at: Utc::now(),
+            value: value.clone(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Where the snapshot for `station` lives in `dir`. Station identifiers are letters and
+    /// digits, and anything else could point outside `dir`, so it has no snapshot.
+    pub fn path(dir: &Path, station: &str) -> Option<PathBuf> {
+        if station.is_empty() || !station.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let mut path = dir.join(station.to_uppercase());
+        path.set_extension("json");
+        Some(path)
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = read_to_string(path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Writes the snapshot through a temporary file, so a crash mid-write cannot leave a
+    /// truncated snapshot behind.
+    pub fn save(&self, path: &Path) -> Option<()> {
+        let dir = path.parent()?;
+        create_dir_all(dir).ok()?;
+        let tmp = path.with_extension("json.tmp");
+        write(&tmp, serde_json::to_string(self).ok()?).ok()?;
+        rename(&tmp, path).ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observation.is_none()
+            && self.station.is_none()
+            && self.alerts.is_none()
+            && self.forecast.is_none()
+            && self.hourly_forecast.is_none()
+    }
+
+    /// When the newest data in the snapshot was fetched.
+    pub fn fetched_at(&self) -> Option<DateTime<Utc>> {
+        [
+            self.observation.as_ref().map(|saved| saved.fetched_at),
+            self.station.as_ref().map(|saved| saved.fetched_at),
+            self.alerts.as_ref().map(|saved| saved.fetched_at),
+            self.forecast.as_ref().map(|saved| saved.fetched_at),
+            self.hourly_forecast.as_ref().map(|saved| saved.fetched_at),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+/// The NWS API, saving each fetch to the snapshots in `dir` only when the client is live.
+/// Recorded or replayed runs would otherwise pass canned responses off as fresh data later.
+pub fn nws(client: noaa::Client, dir: Option<PathBuf>) -> Box<dyn WeatherSource> {
+    match dir {
+        Some(dir) if client.is_live() => Box::new(Persisting::new(Nws::new(client), dir)),
+        _ => Box::new(Nws::new(client)),
+    }
+}
+
+/// Saves every successful fetch from `inner` to the station's snapshot file in a directory.
+pub struct Persisting<S> {
+    inner: S,
+    dir: PathBuf,
+}
+
+impl<S> Persisting<S> {
+    pub fn new(inner: S, dir: PathBuf) -> Self {
+        Self { inner, dir }
+    }
+
+    fn save<T: Clone>(
+        &self,
+        station_id: &str,
+        result: Result<T, noaa::Error>,
+        set: impl FnOnce(&mut Snapshot, Saved<T>),
+    ) -> Result<T, noaa::Error> {
+        if let (Ok(value), Some(path)) = (&result, Snapshot::path(&self.dir, station_id)) {
+            let mut snapshot = Snapshot::load(&path).unwrap_or_default();
+            set(&mut snapshot, Saved::now(value));
+            snapshot.save(&path);
+        }
+        result
+    }
+}
+
+impl<S: WeatherSource> WeatherSource for Persisting<S> {
+    fn station(&self, station_id: &str) -> Result<Station, noaa::Error> {
+        let result = self.inner.station(station_id);
+        self.save(station_id, result, |snapshot, saved| {
+            snapshot.station = Some(saved)
+        })
+    }
+
+    fn observation(&self, station_id: &str) -> Result<Observation, noaa::Error> {
+        let result = self.inner.observation(station_id);
+        self.save(station_id, result, |snapshot, saved| {
+            snapshot.observation = Some(saved)
+        })
+    }
+
+    fn alerts(&self, station: &Station) -> Result<Alerts, noaa::Error> {
+        let result = self.inner.alerts(station);
+        self.save(station.id(), result, |snapshot, saved| {
+            snapshot.alerts = Some(saved)
+        })
+    }
+
+    fn forecast(&self, station: &Station) -> Result<Forecast, noaa::Error> {
+        let result = self.inner.forecast(station);
+        self.save(station.id(), result, |snapshot, saved| {
+            snapshot.forecast = Some(saved)
+        })
+    }
+
+    fn hourly_forecast(&self, station: &Station) -> Result<Forecast, noaa::Error> {
+        let result = self.inner.hourly_forecast(station);
+        self.save(station.id(), result, |snapshot, saved| {
+            snapshot.hourly_forecast = Some(saved)
+        })
+    }
+
+    fn nearby_stations(&self, station: &Station) -> Result<Vec<Station>, noaa::Error> {
+        self.inner.nearby_stations(station)
+    }
+
+    fn nearby_observation(&self, station_id: &str) -> Result<Observation, noaa::Error> {
+        self.inner.nearby_observation(station_id)
+    }
+
+    fn stats(&self) -> noaa::Stats {
+        self.inner.stats()
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let station: Station = serde_json::from_str(
+        r#"{
+            "geometry": {"coordinates": [-89.35, 43.14]},
+            "properties": {
+                "name": "Madison, Dane County Regional Airport",
+                "forecast": "https://api.weather.gov/zones/forecast/WIZ063",
+                "stationIdentifier": "KMSN"
+            }
+        }"#,
+    )
+    .unwrap();
+    let snapshot = Snapshot {
+        station: Some(Saved::now(&station)),
+        ..Default::default()
+    };
+
+    let path = std::env::temp_dir().join(format!("wx-snapshot-{}.json", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let loaded = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let loaded = loaded.station.unwrap().value;
+    assert_eq!(loaded.properties.station_identifier, "KMSN");
+    assert_eq!(loaded.zone_id(), "WIZ063");
+    assert_eq!(loaded.coordinates(), (43.14, -89.35));
+    assert_eq!(
+        snapshot.fetched_at(),
+        snapshot.station.as_ref().map(|saved| saved.fetched_at)
+    );
+}
+
+#[test]
+fn test_replay_is_not_saved() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/KMSN");
+    let transport = noaa::Transport::Replay(fixtures);
+    let client = noaa::Client::new(noaa::DEFAULT_BASE_URL, "wx-test", transport).unwrap();
+    let dir = std::env::temp_dir().join(format!("wx-replay-{}", std::process::id()));
+
+    let source = nws(client, Some(dir.clone()));
+    assert!(source.station("KMSN").is_ok());
+    assert!(source.observation("KMSN").is_ok());
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_nearby_is_not_saved() {
+    let mut demo = crate::source::StaticSource::demo();
+    let mut station = demo.station.clone().unwrap();
+    station.properties.station_identifier = "KMKX".to_string();
+    demo.nearby = vec![(station, demo.observation.clone().unwrap())];
+    let dir = std::env::temp_dir().join(format!("wx-nearby-{}", std::process::id()));
+
+    let source = Persisting::new(demo, dir.clone());
+    assert!(source.nearby_observation("KMKX").is_ok());
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_snapshot_path() {
+    let dir = Path::new("/state/wx/snapshots");
+    assert_eq!(Snapshot::path(dir, "kmsn"), Some(dir.join("KMSN.json")));
+    for station in ["", "../x", "/etc/passwd", "K MSN", "KMSN.json"] {
+        assert_eq!(Snapshot::path(dir, station), None, "{station:?}");
+    }
+}
@@ -0,0 +1,269 @@
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+
+use crate::noaa::{
+    self,
+    alerts::Alerts,
+    forecast::Forecast,
+    gridpoints::Gridpoints,
+    observation::Observation,
+    station::{Station, Stations},
+};
+
+/// Where the app gets its weather from.
+pub trait WeatherSource: Send {
+    fn station(&self, station_id: &str) -> Result<Station, noaa::Error>;
+
+    fn observation(&self, station_id: &str) -> Result<Observation, noaa::Error>;
+
+    fn alerts(&self, station: &Station) -> Result<Alerts, noaa::Error>;
+
+    fn forecast(&self, station: &Station) -> Result<Forecast, noaa::Error>;
+
+    fn hourly_forecast(&self, station: &Station) -> Result<Forecast, noaa::Error>;
+
+    /// Observation stations near `station`, nearest first. May include `station` itself.
+    fn nearby_stations(&self, station: &Station) -> Result<Vec<Station>, noaa::Error>;
+
+    /// The latest observation of a nearby station, for filling in. Unlike
+    /// [`WeatherSource::observation`] it isn't saved, as the user didn't ask for the station.
+    fn nearby_observation(&self, station_id: &str) -> Result<Observation, noaa::Error> {
+        self.observation(station_id)
+    }
+
+    /// Request statistics for the status line.
+    fn stats(&self) -> noaa::Stats {
+        noaa::Stats::default()
+    }
+}
+
+/// The National Weather Service API.
+pub struct Nws {
+    client: noaa::Client,
+
+    /// The gridpoint of the last station, which both forecasts are looked up from.
+    grid: Mutex<Option<((f32, f32), Gridpoints)>>,
+
+    /// The stations near the last station, which hardly ever change.
+    nearby: Mutex<Option<(String, Vec<Station>)>>,
+}
+
+impl Nws {
+    pub fn new(client: noaa::Client) -> Self {
+        Self {
+            client,
+            grid: Mutex::new(None),
+            nearby: Mutex::new(None),
+        }
+    }
+
+    fn gridpoints(&self, station: &Station) -> Result<Gridpoints, noaa::Error> {
+        let coords = station.coordinates();
+        let mut grid = self.grid.lock().unwrap();
+        match *grid {
+            Some((cached, ref points)) if cached == coords => Ok(points.clone()),
+            _ => {
+                let points = Gridpoints::from_coord(&self.client, coords.0, coords.1)?;
+                *grid = Some((coords, points.clone()));
+                Ok(points)
+            }
+        }
+    }
+}
+
+impl WeatherSource for Nws {
+    /// The station, with the forecast office and gridpoint from the points lookup when it
+    /// succeeds. A failed lookup shows up in the forecasts instead.
+    fn station(&self, station_id: &str) -> Result<Station, noaa::Error> {
+        let mut station = Station::from_station(&self.client, station_id)?;
+        station.properties.grid = self.gridpoints(&station).ok().map(|grid| grid.grid());
+        Ok(station)
+    }
+
+    fn observation(&self, station_id: &str) -> Result<Observation, noaa::Error> {
+        Observation::from_station(&self.client, station_id)
+    }
+
+    fn alerts(&self, station: &Station) -> Result<Alerts, noaa::Error> {
+        Alerts::from_noaa(&self.client, station.zone_id())
+    }
+
+    fn forecast(&self, station: &Station) -> Result<Forecast, noaa::Error> {
+        let grid = self.gridpoints(station)?;
+        Forecast::from_noaa(&self.client, grid.forecast_url())
+    }
+
+    fn hourly_forecast(&self, station: &Station) -> Result<Forecast, noaa::Error> {
+        let grid = self.gridpoints(station)?;
+        Forecast::from_noaa(&self.client, grid.forecast_hourly_url())
+    }
+
+    fn nearby_stations(&self, station: &Station) -> Result<Vec<Station>, noaa::Error> {
+        let mut nearby = self.nearby.lock().unwrap();
+        match *nearby {
+            Some((ref id, ref stations)) if id == station.id() => Ok(stations.clone()),
+            _ => {
+                let grid = self.gridpoints(station)?;
+                let stations =
+                    Stations::from_noaa(&self.client, grid.observation_stations_url())?.features;
+                *nearby = Some((station.id().to_owned(), stations.clone()));
+                Ok(stations)
+            }
+        }
+    }
+
+    fn stats(&self) -> noaa::Stats {
+        self.client.stats()
+    }
+}
+
+/// Serves fixed data, for tests and demos. Missing resources fail as if they had never been
+/// recorded.
+#[derive(Debug, Default, Clone)]
+pub struct StaticSource {
+    pub station: Option<Station>,
+    pub observation: Option<Observation>,
+    pub alerts: Option<Alerts>,
+    pub forecast: Option<Forecast>,
+    pub hourly_forecast: Option<Forecast>,
+
+    /// Stations near `station` and their observations, nearest first.
+    pub nearby: Vec<(Station, Observation)>,
+}
+
+impl StaticSource {
+    /// Madison, WI on a frosty October day, from the recordings in `fixtures/KMSN`.
+    pub fn demo() -> Self {
+        let mut station: Station = fixture(include_str!("../fixtures/KMSN/stations_KMSN.json"));
+        let grid: Gridpoints = fixture(include_str!("../fixtures/KMSN/points_43.14,-89.35.json"));
+        station.properties.grid = Some(grid.grid());
+        let mut alerts: Alerts = fixture(include_str!(
+            "../fixtures/KMSN/alerts_active_zone_WIZ063.json"
+        ));
+        let mut forecast: Forecast = fixture(include_str!(
+            "../fixtures/KMSN/gridpoints_MKX_37,63_forecast.json"
+        ));
+
+        // The recordings have none of the long text the details popup shows, so the demo makes
+        // some up.
+        let frost = &mut alerts.features[0].properties;
+        frost.description = Some(
+            "* WHAT...Temperatures as low as 31 will result in frost formation.\n\n\
+             * WHERE...Dane County.\n\n\
+             * WHEN...From 6 PM this evening to 6 AM CDT Sunday.\n\n\
+             * IMPACTS...Frost could harm sensitive outdoor vegetation."
+                .to_string(),
+        );
+        frost.instruction =
+            Some("Take steps now to protect tender plants from the cold.".to_string());
+        let details = [
+            "Mostly cloudy, with a high near 55. Southwest wind around 10 mph, with gusts as high \
+             as 20 mph.",
+            "Patchy frost after 1am. Mostly clear, with a low around 33. Southwest wind 5 to 10 \
+             mph becoming calm.",
+            "Sunny, with a high near 58. South wind 5 to 10 mph.",
+            "Mostly clear, with a low around 41. South wind around 10 mph.",
+            "A chance of showers after 1pm. Mostly cloudy, with a high near 63. Chance of \
+             precipitation is 40%.",
+            "Showers likely. Cloudy, with a low around 47. Chance of precipitation is 70%.",
+        ];
+        for (period, detail) in forecast.properties.periods.iter_mut().zip(details) {
+            period.detailed_forecast = Some(detail.to_string());
+        }
+
+        Self {
+            station: Some(station),
+            observation: Some(fixture(include_str!(
+                "../fixtures/KMSN/stations_KMSN_observations_latest.json"
+            ))),
+            alerts: Some(alerts),
+            forecast: Some(forecast),
+            hourly_forecast: Some(fixture(include_str!(
+                "../fixtures/KMSN/gridpoints_MKX_37,63_forecast_hourly.json"
+            ))),
+            nearby: Vec::new(),
+        }
+    }
+}
+
+/// Decodes the body of a recorded response.
+fn fixture<T: DeserializeOwned>(recording: &str) -> T {
+    let recording: serde_json::Value = serde_json::from_str(recording).unwrap();
+    serde_json::from_str(recording["body"].as_str().unwrap()).unwrap()
+}
+
+fn cloned<T: Clone>(value: &Option<T>, what: &str) -> Result<T, noaa::Error> {
+    value.clone().ok_or_else(|| noaa::Error::NotRecorded {
+        url: what.to_owned(),
+    })
+}
+
+impl WeatherSource for StaticSource {
+    fn station(&self, _station_id: &str) -> Result<Station, noaa::Error> {
+        cloned(&self.station, "station")
+    }
+
+    /// The observation of a nearby station if `station_id` is one, otherwise `observation`.
+    fn observation(&self, station_id: &str) -> Result<Observation, noaa::Error> {
+        match self
+            .nearby
+            .iter()
+            .find(|(station, _)| station.id() == station_id)
+        {
+            Some((_, observation)) => Ok(observation.clone()),
+            None => cloned(&self.observation, "observation"),
+        }
+    }
+
+    fn alerts(&self, _station: &Station) -> Result<Alerts, noaa::Error> {
+        cloned(&self.alerts, "alerts")
+    }
+
+    fn forecast(&self, _station: &Station) -> Result<Forecast, noaa::Error> {
+        cloned(&self.forecast, "forecast")
+    }
+
+    fn hourly_forecast(&self, _station: &Station) -> Result<Forecast, noaa::Error> {
+        cloned(&self.hourly_forecast, "hourly forecast")
+    }
+
+    fn nearby_stations(&self, _station: &Station) -> Result<Vec<Station>, noaa::Error> {
+        Ok(self
+            .nearby
+            .iter()
+            .map(|(station, _)| station.clone())
+            .collect())
+    }
+}
+
+#[test]
+fn test_demo_source() {
+    let source = StaticSource::demo();
+    let station = source.station("KMSN").unwrap();
+    assert_eq!(station.properties.station_identifier, "KMSN");
+    assert_eq!(
+        station.properties.grid,
+        Some(noaa::station::Grid {
+            office: "MKX".to_string(),
+            x: 37,
+            y: 63
+        })
+    );
+    assert_eq!(station.properties.elevation.value, Some(264.0216));
+    assert_eq!(
+        source
+            .hourly_forecast(&station)
+            .unwrap()
+            .properties
+            .periods
+            .len(),
+        24
+    );
+
+    let empty = StaticSource::default();
+    assert!(matches!(
+        empty.observation("KMSN"),
+        Err(noaa::Error::NotRecorded { .. })
+    ));
+}
@@ -0,0 +1,104 @@
+use std::io::{self, Stdout};
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::thread;
+
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal, TerminalOptions, Viewport};
+
+/// The terminal in raw mode, on the alternate screen or in a viewport below the prompt. Dropping
+/// it puts the terminal back the way it was, including when unwinding from a panic.
+pub struct Guard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    inline: bool,
+}
+
+impl Guard {
+    pub fn enter() -> io::Result<Self> {
+        install_panic_hook(false);
+        // Until the guard exists, a failed step has to put the terminal back itself.
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
+            .inspect_err(|_| restore(false))?;
+        let terminal =
+            Terminal::new(CrosstermBackend::new(stdout)).inspect_err(|_| restore(false))?;
+        Ok(Self {
+            terminal,
+            inline: false,
+        })
+    }
+
+    /// Draws in `height` lines below the prompt rather than taking over the screen. The mouse is
+    /// left alone so the scrollback still scrolls.
+    pub fn inline(height: u16) -> io::Result<Self> {
+        install_panic_hook(true);
+        enable_raw_mode()?;
+        let options = TerminalOptions {
+            viewport: Viewport::Inline(height),
+        };
+        let terminal = Terminal::with_options(CrosstermBackend::new(io::stdout()), options)
+            .inspect_err(|_| restore(true))?;
+        Ok(Self {
+            terminal,
+            inline: true,
+        })
+    }
+}
+
+impl Deref for Guard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for Guard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if !self.inline {
+            restore(false);
+            return;
+        }
+        // Leave the last frame in the scrollback, with the prompt on the line below it.
+        let bottom = self.terminal.get_frame().area().bottom();
+        _ = self
+            .terminal
+            .set_cursor_position((0, bottom.saturating_sub(1)));
+        restore(true);
+        println!();
+    }
+}
+
+/// Restores the terminal before the panic message is printed, so it is readable. The guard is
+/// only dropped after the message, and not at all with `panic = "abort"`. Workers' panics are
+/// left to the main thread, which still draws and quits cleanly when told of them.
+fn install_panic_hook(inline: bool) {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            restore(inline);
+        }
+        hook(info);
+    }));
+}
+
+/// Leaves raw mode and, unless `inline`, the alternate screen. Errors are ignored, as there is
+/// nothing better to do with them on the way out. Running it twice is harmless.
+fn restore(inline: bool) {
+    _ = disable_raw_mode();
+    if !inline {
+        _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+    }
+    _ = execute!(io::stdout(), crossterm::cursor::Show);
+}
@@ -0,0 +1,474 @@
+use std::error::Error;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, Config};
+use crate::noaa::alerts::Severity;
+
+const THEME_DIR: &str = "themes";
+
+/// The names of the built-in themes.
+const BUILT_IN: &[&str] = &["default", "light", "high-contrast", "monochrome"];
+
+/// The styles everything is drawn with.
+///
+/// Theme files set styles as strings of a foreground colour, `on` a background colour, and
+/// modifiers, e.g. `title = "magenta bold"` or `banner = "black on #ffd700"`. `extends` names the
+/// theme to start from, `default` if it is left out:
+///
+/// ```toml
+/// extends = "light"
+/// border = "gray"
+///
+/// [temperature]
+/// hot = "light-red bold"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    /// Panel borders.
+    #[serde(with = "spec")]
+    pub border: Style,
+
+    /// The border of the focused panel and of popups.
+    #[serde(with = "spec")]
+    pub focused: Style,
+
+    /// Panel titles and tabs.
+    #[serde(with = "spec")]
+    pub title: Style,
+
+    /// Forecast period names.
+    #[serde(with = "spec")]
+    pub heading: Style,
+
+    #[serde(with = "spec")]
+    pub value: Style,
+
+    /// The station identifier in the headline.
+    #[serde(with = "spec")]
+    pub station: Style,
+
+    /// Errors, and how stale a panel is.
+    #[serde(with = "spec")]
+    pub error: Style,
+
+    /// Notices, such as how long fetching is paused for.
+    #[serde(with = "spec")]
+    pub warning: Style,
+
+    /// The banner shown while offline.
+    #[serde(with = "spec")]
+    pub banner: Style,
+
+    pub temperature: Temperature,
+    pub severity: Severities,
+}
+
+/// Temperatures are coloured by band.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Temperature {
+    /// At or below freezing.
+    #[serde(with = "spec")]
+    pub freezing: Style,
+
+    /// Below 50°F.
+    #[serde(with = "spec")]
+    pub cold: Style,
+
+    /// Below 70°F.
+    #[serde(with = "spec")]
+    pub mild: Style,
+
+    /// Below 85°F.
+    #[serde(with = "spec")]
+    pub warm: Style,
+
+    #[serde(with = "spec")]
+    pub hot: Style,
+}
+
+/// Alerts are coloured by severity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Severities {
+    #[serde(with = "spec")]
+    pub unknown: Style,
+    #[serde(with = "spec")]
+    pub minor: Style,
+    #[serde(with = "spec")]
+    pub moderate: Style,
+    #[serde(with = "spec")]
+    pub severe: Style,
+    #[serde(with = "spec")]
+    pub extreme: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let fg = |color| Style::default().fg(color);
+        Self {
+            border: fg(Color::Cyan),
+            focused: fg(Color::White),
+            title: fg(Color::Yellow),
+            heading: fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            value: fg(Color::Green),
+            station: fg(Color::Blue),
+            error: fg(Color::Red),
+            warning: fg(Color::Yellow),
+            banner: fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            temperature: Temperature {
+                freezing: fg(Color::LightBlue),
+                cold: fg(Color::Cyan),
+                mild: fg(Color::Green),
+                warm: fg(Color::Yellow),
+                hot: fg(Color::Red),
+            },
+            severity: Severities {
+                unknown: fg(Color::Green),
+                minor: fg(Color::Green),
+                moderate: fg(Color::Yellow),
+                severe: fg(Color::Red),
+                extreme: fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            },
+        }
+    }
+}
+
+impl Theme {
+    /// Darker colours that stay readable on a light background.
+    pub fn light() -> Self {
+        let fg = |color| Style::default().fg(color);
+        Self {
+            border: fg(Color::Blue),
+            focused: fg(Color::Black).add_modifier(Modifier::BOLD),
+            title: fg(Color::Magenta),
+            heading: fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            value: fg(Color::Black),
+            station: fg(Color::Blue).add_modifier(Modifier::BOLD),
+            error: fg(Color::Red),
+            warning: fg(Color::Magenta),
+            banner: fg(Color::White)
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            temperature: Temperature {
+                freezing: fg(Color::Blue),
+                cold: fg(Color::Cyan),
+                mild: fg(Color::Green),
+                warm: fg(Color::Magenta),
+                hot: fg(Color::Red),
+            },
+            severity: Severities {
+                unknown: fg(Color::Black),
+                minor: fg(Color::Black),
+                moderate: fg(Color::Magenta),
+                severe: fg(Color::Red).add_modifier(Modifier::BOLD),
+                extreme: fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            },
+        }
+    }
+
+    /// Bright, bold colours.
+    pub fn high_contrast() -> Self {
+        let fg = |color| Style::default().fg(color);
+        let bold = |color| fg(color).add_modifier(Modifier::BOLD);
+        Self {
+            border: fg(Color::White),
+            focused: bold(Color::LightYellow),
+            title: bold(Color::LightYellow),
+            heading: bold(Color::LightYellow).add_modifier(Modifier::UNDERLINED),
+            value: bold(Color::White),
+            station: bold(Color::LightCyan),
+            error: bold(Color::LightRed),
+            warning: bold(Color::LightYellow),
+            banner: bold(Color::Black).bg(Color::LightYellow),
+            temperature: Temperature {
+                freezing: bold(Color::LightBlue),
+                cold: bold(Color::LightCyan),
+                mild: bold(Color::LightGreen),
+                warm: bold(Color::LightYellow),
+                hot: bold(Color::LightRed),
+            },
+            severity: Severities {
+                unknown: bold(Color::White),
+                minor: bold(Color::White),
+                moderate: bold(Color::LightYellow),
+                severe: bold(Color::LightRed),
+                extreme: bold(Color::White).bg(Color::Red),
+            },
+        }
+    }
+
+    /// No colours at all, only bold and reversed text.
+    pub fn monochrome() -> Self {
+        let plain = Style::default();
+        let bold = plain.add_modifier(Modifier::BOLD);
+        Self {
+            border: plain,
+            focused: bold,
+            title: bold,
+            heading: bold,
+            value: plain,
+            station: bold,
+            error: bold,
+            warning: plain,
+            banner: bold.add_modifier(Modifier::REVERSED),
+            temperature: Temperature {
+                freezing: plain,
+                cold: plain,
+                mild: plain,
+                warm: plain,
+                hot: plain,
+            },
+            severity: Severities {
+                unknown: plain,
+                minor: plain,
+                moderate: plain,
+                severe: bold,
+                extreme: bold.add_modifier(Modifier::REVERSED),
+            },
+        }
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// The theme called `name`, either built in or in the config directory's `themes` folder.
+    /// `no_color` (from `NO_COLOR`) overrides it with `monochrome`.
+    pub fn load(name: Option<&str>, no_color: bool) -> Result<Self, Box<dyn Error>> {
+        if no_color {
+            return Ok(Self::monochrome());
+        }
+        let Some(name) = name else {
+            return Ok(Self::default());
+        };
+        if let Some(theme) = Self::built_in(name) {
+            return Ok(theme);
+        }
+        let unknown = || {
+            format!(
+                "no theme named `{name}`; the built-in themes are {}",
+                BUILT_IN.join(", ")
+            )
+        };
+        let path = Self::path(name).ok_or_else(unknown)?;
+        let text = match read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Err(unknown().into()),
+            Err(err) => return Err(format!("{}: {err}", path.display()).into()),
+        };
+        let table = toml::from_str(&text).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::from_table(table).map_err(|err| format!("{}: {err}", path.display()).into())
+    }
+
+    fn path(name: &str) -> Option<PathBuf> {
+        let mut path = Config::dir()?.join(THEME_DIR).join(name);
+        path.set_extension("toml");
+        Some(path)
+    }
+
+    /// Lays a theme file over the theme it extends.
+    fn from_table(mut table: toml::Table) -> Result<Self, String> {
+        let base = match table.remove("extends") {
+            None => Self::default(),
+            Some(toml::Value::String(name)) => {
+                Self::built_in(&name).ok_or_else(|| format!("no built-in theme named `{name}`"))?
+            }
+            Some(value) => return Err(format!("`extends` should be a string, not {value}")),
+        };
+        let mut merged = toml::Table::try_from(base).map_err(|err| err.to_string())?;
+        config::merge(&mut merged, table);
+        toml::Value::Table(merged)
+            .try_into()
+            .map_err(|err: toml::de::Error| err.to_string())
+    }
+
+    /// The style for a temperature in Fahrenheit.
+    pub fn temperature(&self, temp_f: f32) -> Style {
+        let bands = &self.temperature;
+        match temp_f {
+            ..=32.0 => bands.freezing,
+            ..50.0 => bands.cold,
+            ..70.0 => bands.mild,
+            ..85.0 => bands.warm,
+            _ => bands.hot,
+        }
+    }
+
+    pub fn severity(&self, severity: Severity) -> Style {
+        let styles = &self.severity;
+        match severity {
+            Severity::Unknown => styles.unknown,
+            Severity::Minor => styles.minor,
+            Severity::Moderate => styles.moderate,
+            Severity::Severe => styles.severe,
+            Severity::Extreme => styles.extreme,
+        }
+    }
+}
+
+/// Whether `NO_COLOR` asks for no colour, i.e. is set to anything but an empty string.
+pub fn no_color(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Styles as strings, e.g. `"yellow on blue bold"`.
+mod spec {
+    use super::*;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(style: &Style, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(*style))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Style, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).map_err(de::Error::custom)
+    }
+
+    pub fn parse(text: &str) -> Result<Style, String> {
+        let mut style = Style::default();
+        let mut words = text.split_whitespace();
+        while let Some(word) = words.next() {
+            if word == "on" {
+                let color = words.next().ok_or("expected a colour after `on`")?;
+                style = style.bg(color_named(color)?);
+            } else if let Some(modifier) =
+                Modifier::from_name(&word.to_uppercase().replace('-', "_"))
+            {
+                style = style.add_modifier(modifier);
+            } else if style.fg.is_none() {
+                style = style.fg(color_named(word)?);
+            } else {
+                return Err(format!("`{text}` has more than one foreground colour"));
+            }
+        }
+        Ok(style)
+    }
+
+    fn color_named(name: &str) -> Result<Color, String> {
+        Color::from_str(name).map_err(|_| format!("unknown colour `{name}`"))
+    }
+
+    pub fn format(style: Style) -> String {
+        let mut words = vec![];
+        if let Some(fg) = style.fg {
+            words.push(fg.to_string().to_lowercase());
+        }
+        if let Some(bg) = style.bg {
+            words.push(format!("on {}", bg.to_string().to_lowercase()));
+        }
+        for (name, _) in style.add_modifier.iter_names() {
+            words.push(name.to_lowercase().replace('_', "-"));
+        }
+        words.join(" ")
+    }
+}
+
+#[test]
+fn test_style_spec() {
+    let style = spec::parse("light-red on #102030 bold crossed-out").unwrap();
+    assert_eq!(
+        style,
+        Style::default()
+            .fg(Color::LightRed)
+            .bg(Color::Rgb(0x10, 0x20, 0x30))
+            .add_modifier(Modifier::BOLD | Modifier::CROSSED_OUT)
+    );
+    assert_eq!(spec::format(style), "lightred on #102030 bold crossed-out");
+    assert_eq!(spec::parse("").unwrap(), Style::default());
+    assert_eq!(
+        spec::parse("red blue").unwrap_err(),
+        "`red blue` has more than one foreground colour"
+    );
+    assert_eq!(
+        spec::parse("on").unwrap_err(),
+        "expected a colour after `on`"
+    );
+    assert_eq!(spec::parse("puce").unwrap_err(), "unknown colour `puce`");
+
+    // Every built-in theme survives the trip through a theme file.
+    for name in BUILT_IN {
+        let theme = Theme::built_in(name).unwrap();
+        let table = toml::Table::try_from(theme.clone()).unwrap();
+        assert_eq!(Theme::from_table(table).unwrap(), theme, "{name}");
+    }
+}
+
+#[test]
+fn test_theme_file() {
+    let table = toml::from_str(
+        r#"
+        extends = "light"
+        border = "gray"
+
+        [temperature]
+        hot = "light-red bold"
+        "#,
+    )
+    .unwrap();
+    let theme = Theme::from_table(table).unwrap();
+    assert_eq!(theme.border, Style::default().fg(Color::Gray));
+    assert_eq!(
+        theme.temperature.hot,
+        Style::default()
+            .fg(Color::LightRed)
+            .add_modifier(Modifier::BOLD)
+    );
+    // Everything else comes from the theme it extends.
+    assert_eq!(theme.title, Theme::light().title);
+    assert_eq!(theme.temperature.cold, Theme::light().temperature.cold);
+
+    let err = Theme::from_table(toml::from_str("extends = \"sepia\"").unwrap()).unwrap_err();
+    assert_eq!(err, "no built-in theme named `sepia`");
+    let err = Theme::from_table(toml::from_str("boarder = \"red\"").unwrap()).unwrap_err();
+    assert!(err.contains("unknown field `boarder`"), "{err}");
+}
+
+#[test]
+fn test_no_color() {
+    assert!(!no_color(None));
+    assert!(!no_color(Some("")));
+    assert!(no_color(Some("1")));
+    assert_eq!(
+        Theme::load(Some("light"), true).unwrap(),
+        Theme::monochrome()
+    );
+    assert_eq!(Theme::load(None, false).unwrap(), Theme::default());
+    assert_eq!(
+        Theme::load(Some("high-contrast"), false).unwrap(),
+        Theme::high_contrast()
+    );
+    let err = Theme::load(Some("no-such-theme"), false).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("no theme named `no-such-theme`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_temperature_bands() {
+    let theme = Theme::default();
+    assert_eq!(theme.temperature(32.0), theme.temperature.freezing);
+    assert_eq!(theme.temperature(33.0), theme.temperature.cold);
+    assert_eq!(theme.temperature(52.0), theme.temperature.mild);
+    assert_eq!(theme.temperature(84.9), theme.temperature.warm);
+    assert_eq!(theme.temperature(85.0), theme.temperature.hot);
+}
@@ -1,5 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// The units values are shown in. NOAA reports observations in metric and forecasts in imperial,
+/// so both directions are converted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// Fahrenheit and miles per hour.
+    #[default]
+    Imperial,
+
+    /// Celsius and kilometres per hour.
+    Metric,
+}
+
+impl Units {
+    /// Converts a temperature in Celsius.
+    pub fn celsius(self, temp_c: f32) -> f32 {
+        match self {
+            Units::Imperial => temperature::c2f(temp_c),
+            Units::Metric => temp_c,
+        }
+    }
+
+    /// Converts a temperature in Fahrenheit.
+    pub fn fahrenheit(self, temp_f: f32) -> f32 {
+        match self {
+            Units::Imperial => temp_f,
+            Units::Metric => temperature::f2c(temp_f),
+        }
+    }
+
+    /// Converts a speed in kilometres per hour.
+    pub fn kph(self, kph: f32) -> f32 {
+        match self {
+            Units::Imperial => speed::kph2mph(kph),
+            Units::Metric => kph,
+        }
+    }
+
+    /// Converts a speed in miles per hour.
+    pub fn mph(self, mph: f32) -> f32 {
+        match self {
+            Units::Imperial => mph,
+            Units::Metric => speed::mph2kph(mph),
+        }
+    }
+
+    /// Converts a pressure in pascals.
+    pub fn pascals(self, pa: f32) -> f32 {
+        match self {
+            Units::Imperial => pressure::pa2inhg(pa),
+            Units::Metric => pressure::pa2hpa(pa),
+        }
+    }
+
+    pub fn temperature_label(self) -> &'static str {
+        match self {
+            Units::Imperial => "F",
+            Units::Metric => "C",
+        }
+    }
+
+    pub fn speed_label(self) -> &'static str {
+        match self {
+            Units::Imperial => "MPH",
+            Units::Metric => "KM/H",
+        }
+    }
+
+    pub fn pressure_label(self) -> &'static str {
+        match self {
+            Units::Imperial => "inHg",
+            Units::Metric => "hPa",
+        }
+    }
+
+    /// Converts a height in metres.
+    pub fn metres(self, m: f32) -> f32 {
+        match self {
+            Units::Imperial => length::m2ft(m),
+            Units::Metric => m,
+        }
+    }
+
+    pub fn height_label(self) -> &'static str {
+        match self {
+            Units::Imperial => "ft",
+            Units::Metric => "m",
+        }
+    }
+}
+
 pub mod temperature {
-    #[allow(unused)]
     pub fn f2c(temp_f: f32) -> f32 {
         (temp_f - 32.0) * 5.0 / 9.0
     }
@@ -18,15 +110,54 @@ pub mod temperature {
 }
 
 pub mod speed {
+    /// Miles in a kilometre.
     const KPM: f32 = 0.621371;
 
     pub fn kph2mph(kph: f32) -> f32 {
-        kph / KPM
+        kph * KPM
     }
 
-    #[expect(unused)]
     pub fn mph2kph(mph: f32) -> f32 {
-        mph * KPM
+        mph / KPM
+    }
+
+    #[test]
+    fn test_speed() {
+        assert!((kph2mph(100.0) - 62.1371).abs() < 0.001);
+        assert!((mph2kph(62.1371) - 100.0).abs() < 0.001);
+    }
+}
+
+pub mod pressure {
+    /// Pascals in an inch of mercury.
+    const PA_PER_INHG: f32 = 3386.389;
+
+    pub fn pa2inhg(pa: f32) -> f32 {
+        pa / PA_PER_INHG
+    }
+
+    pub fn pa2hpa(pa: f32) -> f32 {
+        pa / 100.0
+    }
+
+    #[test]
+    fn test_pressure() {
+        assert!((pa2inhg(101325.0) - 29.921).abs() < 0.001);
+        assert_eq!(pa2hpa(101325.0), 1013.25);
+    }
+}
+
+pub mod length {
+    /// Metres in a foot.
+    const M_PER_FT: f32 = 0.3048;
+
+    pub fn m2ft(m: f32) -> f32 {
+        m / M_PER_FT
+    }
+
+    #[test]
+    fn test_length() {
+        assert!((m2ft(264.0216) - 866.2).abs() < 0.1);
     }
 }
 